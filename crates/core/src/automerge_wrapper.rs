@@ -1,9 +1,17 @@
 //! Automerge wrapper for mutable documents with CRDT support
 
-use automerge::{Automerge, ObjType, ReadDoc, transaction::Transactable};
+use automerge::{transaction::Transactable, Automerge, ExId, ObjType, ReadDoc};
+use serde::Serialize;
 use serde_json::Value;
 use crate::error::{Error, Result};
 
+/// Map keys written as Automerge `Text` objects instead of scalar strings,
+/// so concurrent edits merge character-by-character (via `splice_text`)
+/// rather than one writer's value clobbering the other's. A post's `text`
+/// body is the prototypical case: two offline edits to the same note
+/// should converge, not last-writer-wins.
+const TEXT_FIELDS: &[&str] = &["text"];
+
 /// Wrapper around an Automerge document for mutable records
 /// 
 /// This provides a simplified JSON-compatible interface to Automerge
@@ -25,33 +33,20 @@ impl AutomergeDoc {
     }
 
     /// Create a document from JSON value
-    /// 
+    ///
     /// Note: This stores the JSON value and uses Automerge for merging
     pub fn from_json(value: &Value) -> Result<Self> {
         let mut doc = Automerge::new();
-        
-        // For now, store as simple map at root for JSON objects
+
         if let Value::Object(map) = value {
             let mut tx = doc.transaction();
             for (key, val) in map {
-                if let Value::String(s) = val {
-                    tx.put(&automerge::ROOT, key.as_str(), s.as_str())
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                } else if let Some(i) = val.as_i64() {
-                    tx.put(&automerge::ROOT, key.as_str(), i)
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                } else if let Some(f) = val.as_f64() {
-                    tx.put(&automerge::ROOT, key.as_str(), f)
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                } else if let Some(b) = val.as_bool() {
-                    tx.put(&automerge::ROOT, key.as_str(), b)
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                }
+                put_map_value(&mut tx, &automerge::ROOT, key, val)?;
             }
             tx.commit();
         }
 
-        Ok(AutomergeDoc { 
+        Ok(AutomergeDoc {
             doc,
             cached_json: Some(value.clone()),
         })
@@ -76,28 +71,16 @@ impl AutomergeDoc {
     pub fn update(&mut self, value: &Value) -> Result<()> {
         // Update cache
         self.cached_json = Some(value.clone());
-        
+
         // Update Automerge doc
         if let Value::Object(map) = value {
             let mut tx = self.doc.transaction();
             for (key, val) in map {
-                if let Value::String(s) = val {
-                    tx.put(&automerge::ROOT, key.as_str(), s.as_str())
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                } else if let Some(i) = val.as_i64() {
-                    tx.put(&automerge::ROOT, key.as_str(), i)
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                } else if let Some(f) = val.as_f64() {
-                    tx.put(&automerge::ROOT, key.as_str(), f)
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                } else if let Some(b) = val.as_bool() {
-                    tx.put(&automerge::ROOT, key.as_str(), b)
-                        .map_err(|e| Error::AutomergeError(e.to_string()))?;
-                }
+                put_map_value(&mut tx, &automerge::ROOT, key, val)?;
             }
             tx.commit();
         }
-        
+
         Ok(())
     }
 
@@ -106,36 +89,147 @@ impl AutomergeDoc {
         if let Some(ref cached) = self.cached_json {
             return Ok(cached.clone());
         }
-        
-        // Fallback: extract from Automerge
-        let mut map = serde_json::Map::new();
-        
-        // The Automerge 0.6 API returns Result for object_type
-        if let Ok(obj_type) = self.doc.object_type(&automerge::ROOT) {
-            if obj_type == ObjType::Map {
-                for item in self.doc.map_range(&automerge::ROOT, ..) {
-                    let key = item.key.to_string();
+
+        // Fallback: walk the Automerge tree, recursing into nested
+        // maps/lists rather than only reading root-level scalars.
+        self.object_to_json(&automerge::ROOT)
+    }
+
+    /// Materialize the subtree rooted at `obj` as a JSON value, recursing
+    /// into child maps/lists via their object ids.
+    fn object_to_json(&self, obj: &ExId) -> Result<Value> {
+        let obj_type = self
+            .doc
+            .object_type(obj)
+            .map_err(|e| Error::AutomergeError(e.to_string()))?;
+
+        match obj_type {
+            ObjType::Map | ObjType::Table => {
+                let mut map = serde_json::Map::new();
+                for item in self.doc.map_range(obj, ..) {
                     let val = match item.value {
                         automerge::Value::Scalar(ref s) => Self::scalar_to_json(s),
-                        _ => Value::Null,
+                        automerge::Value::Object(_) => self.object_to_json(&item.id)?,
                     };
-                    map.insert(key, val);
+                    map.insert(item.key.to_string(), val);
                 }
+                Ok(Value::Object(map))
+            }
+            ObjType::List => {
+                let mut items = Vec::new();
+                for item in self.doc.list_range(obj, ..) {
+                    let val = match item.value {
+                        automerge::Value::Scalar(ref s) => Self::scalar_to_json(s),
+                        automerge::Value::Object(_) => self.object_to_json(&item.id)?,
+                    };
+                    items.push(val);
+                }
+                Ok(Value::Array(items))
+            }
+            ObjType::Text => {
+                let text = self
+                    .doc
+                    .text(obj)
+                    .map_err(|e| Error::AutomergeError(e.to_string()))?;
+                Ok(Value::String(text))
             }
         }
-        
-        Ok(Value::Object(map))
     }
 
-    /// Merge another document into this one
-    pub fn merge(&mut self, other: &mut AutomergeDoc) -> Result<()> {
+    /// Create a counter field named `key` inside the map at `path`,
+    /// starting at `start`. Unlike a plain integer written with `put`,
+    /// concurrent `increment` calls from different replicas both apply and
+    /// sum on merge rather than one overwriting the other.
+    pub fn init_counter(&mut self, path: &[&str], key: &str, start: i64) -> Result<()> {
+        let obj = self.resolve_path(path)?;
+
+        let mut tx = self.doc.transaction();
+        tx.put(&obj, key, automerge::ScalarValue::counter(start))
+            .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        tx.commit();
+
+        self.cached_json = None;
+        Ok(())
+    }
+
+    /// Increment (or, with a negative `delta`, decrement) the counter field
+    /// named `key` inside the map at `path`. Two replicas incrementing the
+    /// same counter offline both land on merge — the counter sums the
+    /// deltas instead of picking a last writer.
+    pub fn increment(&mut self, path: &[&str], key: &str, delta: i64) -> Result<()> {
+        let obj = self.resolve_path(path)?;
+
+        let mut tx = self.doc.transaction();
+        tx.increment(&obj, key, delta)
+            .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        tx.commit();
+
+        self.cached_json = None;
+        Ok(())
+    }
+
+    /// Walk `path` (a sequence of map keys from the root) to the object id
+    /// of the field it names, for use by the counter and `Text`-object
+    /// accessors.
+    fn resolve_path(&self, path: &[&str]) -> Result<ExId> {
+        let mut obj = automerge::ROOT;
+        for key in path {
+            let (_, child) = self
+                .doc
+                .get(&obj, *key)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?
+                .ok_or_else(|| Error::NotFound(format!("No such field: {}", key)))?;
+            obj = child;
+        }
+        Ok(obj)
+    }
+
+    /// Splice the `Text` object at `path`: delete `delete` characters
+    /// starting at `pos`, then insert `insert`. Concurrent splices from two
+    /// replicas to the same text field merge at the grapheme level instead
+    /// of one replacing the other's edit wholesale.
+    pub fn splice_text(&mut self, path: &[&str], pos: usize, delete: usize, insert: &str) -> Result<()> {
+        let obj = self.resolve_path(path)?;
+
+        let mut tx = self.doc.transaction();
+        tx.splice_text(&obj, pos, delete as isize, insert)
+            .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        tx.commit();
+
+        self.cached_json = None;
+        Ok(())
+    }
+
+    /// Read the current value of the `Text` object at `path`.
+    pub fn get_text(&self, path: &[&str]) -> Result<String> {
+        let obj = self.resolve_path(path)?;
+        self.doc
+            .text(&obj)
+            .map_err(|e| Error::AutomergeError(e.to_string()))
+    }
+
+    /// Read the value of the `Text` object at `path` as of `heads`, for
+    /// inspecting history without mutating the live document.
+    pub fn text_at(&self, path: &[&str], heads: &[automerge::ChangeHash]) -> Result<String> {
+        let obj = self.resolve_path(path)?;
+        self.doc
+            .text_at(&obj, heads)
+            .map_err(|e| Error::AutomergeError(e.to_string()))
+    }
+
+    /// Merge another document into this one, returning the patches the
+    /// merge produced so a caller can apply minimal incremental updates
+    /// (e.g. to a DOM) instead of re-reading the whole document.
+    pub fn merge(&mut self, other: &mut AutomergeDoc) -> Result<Vec<Patch>> {
+        let before = self.doc.get_heads();
+
         self.doc.merge(&mut other.doc)
             .map_err(|e| Error::AutomergeError(format!("Merge failed: {}", e)))?;
-        
+
         // Clear cache after merge
         self.cached_json = None;
-        
-        Ok(())
+
+        self.diff_since(&before)
     }
 
     /// Get the list of changes since the given heads
@@ -143,15 +237,71 @@ impl AutomergeDoc {
         self.doc.get_changes(have_deps).into_iter().cloned().collect()
     }
 
-    /// Apply changes to the document
-    pub fn apply_changes(&mut self, changes: Vec<automerge::Change>) -> Result<()> {
+    /// Apply changes to the document, returning the patches they produced
+    /// (see [`merge`](Self::merge)).
+    pub fn apply_changes(&mut self, changes: Vec<automerge::Change>) -> Result<Vec<Patch>> {
+        let before = self.doc.get_heads();
+
         self.doc.apply_changes(changes)
             .map_err(|e| Error::AutomergeError(format!("Failed to apply changes: {}", e)))?;
-        
+
         // Clear cache after applying changes
         self.cached_json = None;
-        
-        Ok(())
+
+        self.diff_since(&before)
+    }
+
+    /// Diff the document against `before`, translating Automerge's own
+    /// patch representation into our simplified [`Patch`] type.
+    fn diff_since(&self, before: &[automerge::ChangeHash]) -> Result<Vec<Patch>> {
+        let after = self.doc.get_heads();
+        Ok(self
+            .doc
+            .diff(before, &after)
+            .into_iter()
+            .map(Self::convert_patch)
+            .collect())
+    }
+
+    /// Translate an Automerge `Patch` (path of object/prop pairs, plus an
+    /// action carrying Automerge's own value types) into our JSON-facing
+    /// [`Patch`], the same simplification `scalar_to_json` applies to
+    /// individual scalars.
+    fn convert_patch(patch: automerge::Patch) -> Patch {
+        let path = patch
+            .path
+            .iter()
+            .map(|(_, prop)| match prop {
+                automerge::Prop::Map(key) => key.clone(),
+                automerge::Prop::Seq(index) => index.to_string(),
+            })
+            .collect();
+
+        let action = match patch.action {
+            automerge::PatchAction::PutMap { value, .. } => PatchAction::Put(Self::value_to_json(&value.0)),
+            automerge::PatchAction::PutSeq { value, .. } => PatchAction::Put(Self::value_to_json(&value.0)),
+            automerge::PatchAction::Insert { values, .. } => PatchAction::Insert(
+                values.iter().map(|(value, ..)| Self::value_to_json(value)).collect(),
+            ),
+            automerge::PatchAction::DeleteMap { .. } | automerge::PatchAction::DeleteSeq { .. } => {
+                PatchAction::Delete
+            }
+            automerge::PatchAction::Increment { value, .. } => PatchAction::Increment(value),
+            _ => PatchAction::Put(Value::Null),
+        };
+
+        Patch { path, action }
+    }
+
+    /// Convert an Automerge `Value` (scalar or object reference) to JSON.
+    /// Object values surface as `Null` here — the patch only reports that
+    /// something new was created at this path; read its contents with
+    /// `to_json`/`get_text` if needed.
+    fn value_to_json(value: &automerge::Value<'_>) -> Value {
+        match value {
+            automerge::Value::Scalar(s) => Self::scalar_to_json(s),
+            automerge::Value::Object(_) => Value::Null,
+        }
     }
 
     /// Get the current document heads (for change tracking)
@@ -159,6 +309,107 @@ impl AutomergeDoc {
         self.doc.get_heads()
     }
 
+    /// Look up a single change by its hash, e.g. to inspect one entry
+    /// surfaced by [`get_history`](Self::get_history) in more detail.
+    pub fn get_change_by_hash(&self, hash: &automerge::ChangeHash) -> Option<automerge::Change> {
+        self.doc.get_change_by_hash(hash).cloned()
+    }
+
+    /// List every change in the document in (one valid) causal order, as
+    /// lightweight metadata rather than the full change bytes — enough for a
+    /// UI to render a history list and let the user pick a revision to view
+    /// with [`to_json_at`](Self::to_json_at).
+    pub fn get_history(&self) -> Vec<ChangeMeta> {
+        self.doc
+            .get_changes(&[])
+            .into_iter()
+            .map(|change| ChangeMeta {
+                hash: change.hash(),
+                actor: change.actor_id().to_string(),
+                timestamp: change.timestamp(),
+                message: change.message().cloned(),
+                deps: change.deps().to_vec(),
+            })
+            .collect()
+    }
+
+    /// Reconstruct the document's JSON representation as of `heads`,
+    /// without disturbing the live (current) state — time-travel for
+    /// scrubbing through a post or profile's prior versions.
+    pub fn to_json_at(&self, heads: &[automerge::ChangeHash]) -> Result<Value> {
+        self.object_to_json_at(&automerge::ROOT, heads)
+    }
+
+    /// `*_at` counterpart to [`object_to_json`](Self::object_to_json):
+    /// reads the subtree rooted at `obj` as it existed at `heads` rather
+    /// than the current state, using the `map_range_at`/`list_range_at`/
+    /// `text_at` query variants.
+    fn object_to_json_at(&self, obj: &ExId, heads: &[automerge::ChangeHash]) -> Result<Value> {
+        let obj_type = self
+            .doc
+            .object_type(obj)
+            .map_err(|e| Error::AutomergeError(e.to_string()))?;
+
+        match obj_type {
+            ObjType::Map | ObjType::Table => {
+                let mut map = serde_json::Map::new();
+                for item in self.doc.map_range_at(obj, .., heads) {
+                    let val = match item.value {
+                        automerge::Value::Scalar(ref s) => Self::scalar_to_json(s),
+                        automerge::Value::Object(_) => self.object_to_json_at(&item.id, heads)?,
+                    };
+                    map.insert(item.key.to_string(), val);
+                }
+                Ok(Value::Object(map))
+            }
+            ObjType::List => {
+                let mut items = Vec::new();
+                for item in self.doc.list_range_at(obj, .., heads) {
+                    let val = match item.value {
+                        automerge::Value::Scalar(ref s) => Self::scalar_to_json(s),
+                        automerge::Value::Object(_) => self.object_to_json_at(&item.id, heads)?,
+                    };
+                    items.push(val);
+                }
+                Ok(Value::Array(items))
+            }
+            ObjType::Text => {
+                let text = self
+                    .doc
+                    .text_at(obj, heads)
+                    .map_err(|e| Error::AutomergeError(e.to_string()))?;
+                Ok(Value::String(text))
+            }
+        }
+    }
+
+    /// Generate the next sync message to send to the peer tracked by
+    /// `session`, or `None` once that peer has converged and there is
+    /// nothing left worth sending.
+    pub fn generate_sync_message(&mut self, session: &mut SyncSession) -> Option<Vec<u8>> {
+        self.doc
+            .generate_sync_message(&mut session.state)
+            .map(|message| message.encode())
+    }
+
+    /// Apply a sync message received from the peer tracked by `session`,
+    /// updating both the document and that peer's sync state so the next
+    /// `generate_sync_message` call only sends what's still missing.
+    pub fn receive_sync_message(&mut self, session: &mut SyncSession, msg: &[u8]) -> Result<()> {
+        let message = automerge::sync::Message::decode(msg)
+            .map_err(|e| Error::AutomergeError(format!("Invalid sync message: {}", e)))?;
+
+        self.doc
+            .receive_sync_message(&mut session.state, message)
+            .map_err(|e| Error::AutomergeError(format!("Failed to apply sync message: {}", e)))?;
+
+        // The message may have applied changes, so the cache can no longer
+        // be trusted to reflect the document.
+        self.cached_json = None;
+
+        Ok(())
+    }
+
     /// Convert Automerge scalar to JSON value
     fn scalar_to_json(scalar: &automerge::ScalarValue) -> Value {
         match scalar {
@@ -174,9 +425,9 @@ impl AutomergeDoc {
                     .map(Value::Number)
                     .unwrap_or(Value::Null)
             }
-            automerge::ScalarValue::Counter(_c) => {
-                // Counter - just return 0 as we can't access internal value
-                Value::Number(0.into())
+            automerge::ScalarValue::Counter(c) => {
+                let value: i64 = c.clone().into();
+                Value::Number(value.into())
             }
             automerge::ScalarValue::Timestamp(t) => Value::Number((*t).into()),
             automerge::ScalarValue::Boolean(b) => Value::Bool(*b),
@@ -192,6 +443,175 @@ impl Default for AutomergeDoc {
     }
 }
 
+/// Per-peer state for the Automerge sync protocol.
+///
+/// Wraps `automerge::sync::State`, which records what the peer on the other
+/// end of this session is already known to have (a Bloom filter of shared
+/// changes plus their last-known heads). Keep one `SyncSession` per peer —
+/// a local repo talking to several remote PDSes needs one session each —
+/// and feed it back into [`AutomergeDoc::generate_sync_message`] and
+/// [`AutomergeDoc::receive_sync_message`] so each round only carries the
+/// changes that peer is still missing, instead of resending full history.
+#[derive(Debug, Default)]
+pub struct SyncSession {
+    state: automerge::sync::State,
+}
+
+impl SyncSession {
+    /// Start a fresh sync session, as when a peer is seen for the first
+    /// time or its prior session state wasn't persisted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// What happened at a [`Patch`]'s path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PatchAction {
+    /// A scalar (or the creation of a nested object) was written.
+    Put(Value),
+    /// One or more values were inserted into a list.
+    Insert(Vec<Value>),
+    /// A key or list element was removed.
+    Delete,
+    /// A counter was bumped by this amount.
+    Increment(i64),
+}
+
+/// A single change produced by [`AutomergeDoc::merge`] or
+/// [`AutomergeDoc::apply_changes`], with a root-relative path (map keys and
+/// list indices, indices rendered as their decimal string) down to where it
+/// happened. Letting callers — in particular the web UI — apply these
+/// directly means they don't have to diff the whole document against its
+/// previous JSON snapshot after every sync.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Patch {
+    pub path: Vec<String>,
+    pub action: PatchAction,
+}
+
+/// Lightweight summary of one [`automerge::Change`], as returned by
+/// [`AutomergeDoc::get_history`]. Carries enough to render a history list
+/// and pick a revision — fetch the full `Change` with
+/// [`AutomergeDoc::get_change_by_hash`] if more is needed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangeMeta {
+    pub hash: automerge::ChangeHash,
+    pub actor: String,
+    pub timestamp: i64,
+    pub message: Option<String>,
+    pub deps: Vec<automerge::ChangeHash>,
+}
+
+/// Write `val` into `obj`'s `key`, recursing into a child `Map`/`List`
+/// object for nested `Value::Object`/`Value::Array` instead of dropping
+/// them, so the Automerge tree actually holds the full structure rather
+/// than just its top-level scalars.
+fn put_map_value(
+    tx: &mut automerge::transaction::Transaction,
+    obj: &ExId,
+    key: &str,
+    val: &Value,
+) -> Result<()> {
+    match val {
+        Value::Object(map) => {
+            let child = tx
+                .put_object(obj, key, ObjType::Map)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            for (k, v) in map {
+                put_map_value(tx, &child, k, v)?;
+            }
+        }
+        Value::Array(items) => {
+            let child = tx
+                .put_object(obj, key, ObjType::List)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            for (i, item) in items.iter().enumerate() {
+                put_list_value(tx, &child, i, item)?;
+            }
+        }
+        Value::String(s) if TEXT_FIELDS.contains(&key) => {
+            let child = tx
+                .put_object(obj, key, ObjType::Text)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            tx.splice_text(&child, 0, 0, s.as_str())
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        }
+        Value::String(s) => {
+            tx.put(obj, key, s.as_str())
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        }
+        Value::Bool(b) => {
+            tx.put(obj, key, *b)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        }
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                tx.put(obj, key, i)
+                    .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            } else if let Some(f) = n.as_f64() {
+                tx.put(obj, key, f)
+                    .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            }
+        }
+        Value::Null => {
+            tx.put(obj, key, automerge::ScalarValue::Null)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+/// List counterpart to [`put_map_value`]: inserts `val` at `index` instead
+/// of under a map key, recursing the same way for nested objects/arrays.
+fn put_list_value(
+    tx: &mut automerge::transaction::Transaction,
+    obj: &ExId,
+    index: usize,
+    val: &Value,
+) -> Result<()> {
+    match val {
+        Value::Object(map) => {
+            let child = tx
+                .insert_object(obj, index, ObjType::Map)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            for (k, v) in map {
+                put_map_value(tx, &child, k, v)?;
+            }
+        }
+        Value::Array(items) => {
+            let child = tx
+                .insert_object(obj, index, ObjType::List)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            for (i, item) in items.iter().enumerate() {
+                put_list_value(tx, &child, i, item)?;
+            }
+        }
+        Value::String(s) => {
+            tx.insert(obj, index, s.as_str())
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        }
+        Value::Bool(b) => {
+            tx.insert(obj, index, *b)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        }
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                tx.insert(obj, index, i)
+                    .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            } else if let Some(f) = n.as_f64() {
+                tx.insert(obj, index, f)
+                    .map_err(|e| Error::AutomergeError(e.to_string()))?;
+            }
+        }
+        Value::Null => {
+            tx.insert(obj, index, automerge::ScalarValue::Null)
+                .map_err(|e| Error::AutomergeError(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,7 +706,12 @@ mod tests {
         });
 
         let doc = AutomergeDoc::from_json(&value).unwrap();
-        let result = doc.to_json().unwrap();
+
+        // Round-trip through save/load so `to_json` has to rebuild the
+        // structure by walking the Automerge tree rather than returning
+        // `cached_json` (which `load` never populates).
+        let loaded = AutomergeDoc::load(&doc.save()).unwrap();
+        let result = loaded.to_json().unwrap();
 
         assert_eq!(result["user"]["name"], "Bob");
         assert_eq!(result["user"]["profile"]["bio"], "Developer");
@@ -301,9 +726,196 @@ mod tests {
         });
 
         let doc = AutomergeDoc::from_json(&value).unwrap();
-        let result = doc.to_json().unwrap();
+        let loaded = AutomergeDoc::load(&doc.save()).unwrap();
+        let result = loaded.to_json().unwrap();
 
         assert!(result["tags"].is_array());
+        assert_eq!(result["tags"][1], "wasm");
         assert!(result["counts"].is_array());
+        assert_eq!(result["counts"][4], 5);
+    }
+
+    #[test]
+    fn test_nested_structures_survive_merge() {
+        // Two replicas each add a different nested field; after merging,
+        // a field from each side must be present in the merged tree — this
+        // only holds if nested objects are written into the Automerge
+        // document itself rather than just kept in `cached_json`.
+        let mut doc1 = AutomergeDoc::from_json(&json!({
+            "user": { "name": "Bob" }
+        }))
+        .unwrap();
+        let mut doc2 = AutomergeDoc::from_json(&json!({
+            "user": { "name": "Bob" },
+            "tags": ["rust", "wasm"]
+        }))
+        .unwrap();
+
+        doc1.merge(&mut doc2).unwrap();
+
+        let result = doc1.to_json().unwrap();
+        assert_eq!(result["user"]["name"], "Bob");
+        assert_eq!(result["tags"][0], "rust");
+    }
+
+    #[test]
+    fn test_sync_converges_without_full_history() {
+        let mut local = AutomergeDoc::from_json(&json!({ "title": "Local post" })).unwrap();
+        let mut remote = AutomergeDoc::new();
+
+        let mut local_session = SyncSession::new();
+        let mut remote_session = SyncSession::new();
+
+        // Exchange messages back and forth until both sides stop producing
+        // one — the standard sync-protocol drain loop.
+        loop {
+            let mut progressed = false;
+
+            if let Some(msg) = local.generate_sync_message(&mut local_session) {
+                remote
+                    .receive_sync_message(&mut remote_session, &msg)
+                    .unwrap();
+                progressed = true;
+            }
+            if let Some(msg) = remote.generate_sync_message(&mut remote_session) {
+                local
+                    .receive_sync_message(&mut local_session, &msg)
+                    .unwrap();
+                progressed = true;
+            }
+
+            if !progressed {
+                break;
+            }
+        }
+
+        assert_eq!(remote.to_json().unwrap()["title"], "Local post");
+
+        // Once converged, neither side has anything new left to say.
+        assert!(local.generate_sync_message(&mut local_session).is_none());
+        assert!(remote.generate_sync_message(&mut remote_session).is_none());
+    }
+
+    #[test]
+    fn test_text_field_round_trips_and_splices() {
+        let mut doc = AutomergeDoc::from_json(&json!({ "text": "Hello world" })).unwrap();
+
+        assert_eq!(doc.get_text(&["text"]).unwrap(), "Hello world");
+        assert_eq!(doc.to_json().unwrap()["text"], "Hello world");
+
+        // Replace "world" with "Rust".
+        doc.splice_text(&["text"], 6, 5, "Rust").unwrap();
+        assert_eq!(doc.get_text(&["text"]).unwrap(), "Hello Rust");
+        assert_eq!(doc.to_json().unwrap()["text"], "Hello Rust");
+    }
+
+    #[test]
+    fn test_concurrent_text_edits_merge_at_character_level() {
+        // Both replicas start from the same saved state, then each edits a
+        // different part of the same text field offline before merging.
+        let base = AutomergeDoc::from_json(&json!({ "text": "Hello world" })).unwrap();
+
+        let mut doc1 = AutomergeDoc::load(&base.save()).unwrap();
+        let mut doc2 = AutomergeDoc::load(&base.save()).unwrap();
+
+        doc1.splice_text(&["text"], 0, 5, "Howdy").unwrap();
+        doc2.splice_text(&["text"], 6, 5, "Rust").unwrap();
+
+        doc1.merge(&mut doc2).unwrap();
+
+        // Both edits survive in the merged text rather than one clobbering
+        // the other, the way a scalar-string last-writer-wins merge would.
+        let merged = doc1.get_text(&["text"]).unwrap();
+        assert!(merged.starts_with("Howdy"));
+        assert!(merged.ends_with("Rust"));
+    }
+
+    #[test]
+    fn test_counter_increments_and_round_trips() {
+        let mut doc = AutomergeDoc::new();
+        doc.init_counter(&[], "likes", 0).unwrap();
+
+        doc.increment(&[], "likes", 1).unwrap();
+        doc.increment(&[], "likes", 1).unwrap();
+
+        assert_eq!(doc.to_json().unwrap()["likes"], 2);
+    }
+
+    #[test]
+    fn test_concurrent_increments_sum_on_merge() {
+        let mut base = AutomergeDoc::new();
+        base.init_counter(&[], "likes", 0).unwrap();
+
+        let mut doc1 = AutomergeDoc::load(&base.save()).unwrap();
+        let mut doc2 = AutomergeDoc::load(&base.save()).unwrap();
+
+        doc1.increment(&[], "likes", 1).unwrap();
+        doc2.increment(&[], "likes", 1).unwrap();
+        doc2.increment(&[], "likes", 1).unwrap();
+
+        doc1.merge(&mut doc2).unwrap();
+
+        // Each replica's increments both apply and sum, unlike a plain
+        // `put` field where the merge would pick one writer's value.
+        assert_eq!(doc1.to_json().unwrap()["likes"], 3);
+    }
+
+    #[test]
+    fn test_merge_returns_patches_for_new_fields() {
+        let mut doc1 = AutomergeDoc::from_json(&json!({ "name": "Alice" })).unwrap();
+        let mut doc2 = AutomergeDoc::load(&doc1.save()).unwrap();
+        doc2.update(&json!({ "name": "Alice", "score": 42 })).unwrap();
+
+        let patches = doc1.merge(&mut doc2).unwrap();
+
+        assert!(patches.iter().any(|p| {
+            p.path == vec!["score".to_string()] && matches!(p.action, PatchAction::Put(Value::Number(_)))
+        }));
+    }
+
+    #[test]
+    fn test_apply_changes_returns_patches() {
+        let mut doc1 = AutomergeDoc::from_json(&json!({ "name": "Alice" })).unwrap();
+        let doc2 = AutomergeDoc::load(&doc1.save()).unwrap();
+
+        let changes = doc1.get_changes(&[]);
+
+        let mut fresh = AutomergeDoc::new();
+        let patches = fresh.apply_changes(changes.clone()).unwrap();
+
+        assert!(!patches.is_empty());
+        assert_eq!(fresh.to_json().unwrap()["name"], "Alice");
+        // Sanity check the fixture: loading the same changes elsewhere
+        // produces the same document.
+        assert_eq!(doc2.to_json().unwrap()["name"], "Alice");
+    }
+
+    #[test]
+    fn test_history_lists_changes_in_order() {
+        let mut doc = AutomergeDoc::from_json(&json!({ "title": "Draft" })).unwrap();
+        doc.update(&json!({ "title": "Published" })).unwrap();
+
+        let history = doc.get_history();
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|meta| !meta.actor.is_empty()));
+        // The update's change comes second and depends on the first.
+        assert_eq!(history[1].deps, vec![history[0].hash]);
+
+        let found = doc.get_change_by_hash(&history[0].hash);
+        assert!(found.is_some());
+    }
+
+    #[test]
+    fn test_to_json_at_reconstructs_past_state() {
+        let mut doc = AutomergeDoc::from_json(&json!({ "title": "Draft" })).unwrap();
+        let heads_after_draft = doc.get_heads();
+
+        doc.update(&json!({ "title": "Published" })).unwrap();
+
+        assert_eq!(doc.to_json().unwrap()["title"], "Published");
+        assert_eq!(
+            doc.to_json_at(&heads_after_draft).unwrap()["title"],
+            "Draft"
+        );
     }
 }
@@ -0,0 +1,229 @@
+//! CAR (Content Addressable aRchive) v1 export/import for ATProto sync
+//! interop.
+//!
+//! A length-prefixed DAG-CBOR header (`{"version": 1, "roots": [<cid link>]}`,
+//! with the root encoded as a proper CBOR tag-42 CID link rather than a
+//! base32 string, as the CAR v1 spec requires) is followed by
+//! length-prefixed `(CID, block bytes)` pairs, one per MST node / record /
+//! commit block, each length itself varint (LEB128) encoded as in the CAR
+//! v1 spec.
+//!
+//! Note: blocks in this crate are currently keyed by the hash of their own
+//! serialized bytes for internal round-tripping (see
+//! [`Snapshot::to_car`](crate::snapshot::Snapshot::to_car)), not by the
+//! DAG-CBOR CID a full ATProto implementation would expect a record/commit
+//! to resolve at (e.g. [`Record::cid`](crate::types::Record::cid)) — a real
+//! `com.atproto.sync.getRepo` client cannot yet resolve these blocks by
+//! their advertised CIDs. The header's root CID link is spec-correct; the
+//! block-CID scheme is a self-consistent placeholder pending true
+//! MST-node/record-value block support.
+
+use crate::dagcbor;
+use crate::error::{Error, Result};
+use crate::types::Cid;
+
+pub(crate) fn write_varint(mut n: u64, buf: &mut Vec<u8>) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            buf.push(byte);
+            break;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+pub(crate) fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| Error::SerializationError("Truncated CAR varint".to_string()))?;
+        *pos += 1;
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    Ok(result)
+}
+
+fn read_framed(bytes: &[u8], pos: &mut usize) -> Result<Vec<u8>> {
+    let len = read_varint(bytes, pos)? as usize;
+    let slice = bytes
+        .get(*pos..*pos + len)
+        .ok_or_else(|| Error::SerializationError("Truncated CAR frame".to_string()))?;
+    *pos += len;
+    Ok(slice.to_vec())
+}
+
+fn write_framed(bytes: &[u8], out: &mut Vec<u8>) {
+    write_varint(bytes.len() as u64, out);
+    out.extend_from_slice(bytes);
+}
+
+/// One content-addressed block in a CAR file: a commit, MST node, or
+/// record, keyed by its own CID.
+#[derive(Debug, Clone)]
+pub struct CarBlock {
+    pub cid: Cid,
+    pub data: Vec<u8>,
+}
+
+/// Encode a CAR v1 header: the DAG-CBOR map `{"version": 1, "roots": [root]}`
+/// with `root` written as a genuine CBOR tag-42 CID link (an IPFS/IPLD CID-Link:
+/// tag 42 wrapping a byte string whose first byte is the 0x00 identity
+/// multibase prefix, followed by the CID's raw binary form), so a real
+/// DAG-CBOR/CAR reader resolves it as a link rather than an opaque string.
+fn encode_header(root: &Cid) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    dagcbor::encode_head(5, 2, &mut buf); // map, 2 entries
+
+    dagcbor::encode_head(3, "roots".len() as u64, &mut buf);
+    buf.extend_from_slice(b"roots");
+    dagcbor::encode_head(4, 1, &mut buf); // array of 1
+    encode_cid_link(root, &mut buf)?;
+
+    dagcbor::encode_head(3, "version".len() as u64, &mut buf);
+    buf.extend_from_slice(b"version");
+    dagcbor::encode_head(0, 1, &mut buf);
+
+    Ok(buf)
+}
+
+/// Encode `cid` as a CBOR tag-42 CID link.
+fn encode_cid_link(cid: &Cid, buf: &mut Vec<u8>) -> Result<()> {
+    let cid_bytes = cid.to_bytes()?;
+    let mut link_bytes = Vec::with_capacity(cid_bytes.len() + 1);
+    link_bytes.push(0x00); // identity multibase prefix, per the CID-link convention
+    link_bytes.extend_from_slice(&cid_bytes);
+
+    buf.push(0xd8); // tag, 1-byte value follows
+    buf.push(42);
+    dagcbor::encode_head(2, link_bytes.len() as u64, buf); // byte string
+    buf.extend_from_slice(&link_bytes);
+    Ok(())
+}
+
+/// Serialize a commit root plus its blocks into a CAR v1 byte stream. Each
+/// block is framed as `varint(len(CID bytes) + len(block bytes))` followed
+/// by the CID's raw binary (multihash-structured) form and then the block
+/// bytes, per the CAR v1 spec — not the base32 string form `Cid` displays.
+pub fn write_car(root: &Cid, blocks: &[CarBlock]) -> Result<Vec<u8>> {
+    let header = encode_header(root)?;
+
+    let mut out = Vec::new();
+    write_framed(&header, &mut out);
+    for block in blocks {
+        let cid_bytes = block.cid.to_bytes()?;
+        let mut frame = cid_bytes;
+        frame.extend_from_slice(&block.data);
+        write_framed(&frame, &mut out);
+    }
+    Ok(out)
+}
+
+/// Parse a CAR v1 byte stream written by [`write_car`], returning the
+/// header bytes (DAG-CBOR encoded) and the blocks in file order, verifying
+/// that each block's bytes hash back to the CID it was framed with.
+pub fn read_car(bytes: &[u8]) -> Result<(Vec<u8>, Vec<CarBlock>)> {
+    let mut pos = 0usize;
+    let header = read_framed(bytes, &mut pos)?;
+
+    let mut blocks = Vec::new();
+    while pos < bytes.len() {
+        let frame = read_framed(bytes, &mut pos)?;
+        const CID_LEN: usize = 36; // CIDv1/dag-cbor/sha2-256: 4-byte prefix + 32-byte digest
+        if frame.len() < CID_LEN {
+            return Err(Error::SerializationError(
+                "CAR block frame shorter than a CID".to_string(),
+            ));
+        }
+        let (cid_bytes, data) = frame.split_at(CID_LEN);
+        let cid = Cid::from_cid_bytes(cid_bytes)?;
+
+        let computed = Cid::from_bytes(data);
+        if computed != cid {
+            return Err(Error::SerializationError(format!(
+                "CAR block data does not hash to its CID: {}",
+                cid
+            )));
+        }
+
+        blocks.push(CarBlock {
+            cid,
+            data: data.to_vec(),
+        });
+    }
+
+    Ok((header, blocks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_encodes_root_as_cid_link_not_string() {
+        let root = Cid::from_bytes(b"root");
+        let bytes = write_car(&root, &[]).unwrap();
+        let (header, _blocks) = read_car(&bytes).unwrap();
+
+        // {"roots": [<tag 42 cid-link>], "version": 1} in canonical key order.
+        assert_eq!(header[0], 0xa2); // map, 2 entries
+        assert_eq!(&header[1..7], b"\x65roots"); // text(5) "roots"
+        assert_eq!(header[7], 0x81); // array, 1 entry
+        assert_eq!(header[8], 0xd8); // tag, 1-byte value follows
+        assert_eq!(header[9], 42); // tag 42: CID link
+
+        let root_bytes = root.to_bytes().unwrap();
+        let link_len = root_bytes.len() + 1; // + identity multibase prefix byte
+        // byte string header (length doesn't fit in the 5-bit inline form)
+        assert_eq!(header[10], 0x40 | 24);
+        assert_eq!(header[11], link_len as u8);
+        assert_eq!(header[12], 0x00); // identity multibase prefix
+        assert_eq!(&header[13..13 + root_bytes.len()], &root_bytes[..]);
+    }
+
+    #[test]
+    fn test_round_trip_empty_blocks() {
+        let root = Cid::from_bytes(b"root");
+        let bytes = write_car(&root, &[]).unwrap();
+        let (_header, blocks) = read_car(&bytes).unwrap();
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip_with_blocks() {
+        let root = Cid::from_bytes(b"root");
+        let block = CarBlock {
+            cid: Cid::from_bytes(b"hello mst node"),
+            data: b"hello mst node".to_vec(),
+        };
+        let bytes = write_car(&root, &[block]).unwrap();
+
+        let (_header, blocks) = read_car(&bytes).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].data, b"hello mst node");
+        assert_eq!(blocks[0].cid, Cid::from_bytes(b"hello mst node"));
+    }
+
+    #[test]
+    fn test_read_car_rejects_tampered_block() {
+        let root = Cid::from_bytes(b"root");
+        let block = CarBlock {
+            cid: Cid::from_bytes(b"original"),
+            data: b"original".to_vec(),
+        };
+        let mut bytes = write_car(&root, &[block]).unwrap();
+
+        // Flip the last byte of the block data, leaving the CID untouched.
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+
+        assert!(read_car(&bytes).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Per-record CRDT merge log for resolving concurrent offline `Update`s.
+//!
+//! Opt-in alternative to whole-value overwrite: each `Update` appends an
+//! entry to a per-`(collection, rkey)` causal log instead of clobbering the
+//! stored value, tagged with a monotonic `(version, actor_id)` Lamport stamp.
+//! Reads fold the log in stamp order: last-writer-wins per JSON field for scalars, and
+//! observed-remove set semantics for array membership. A `Delete` appends a
+//! tombstone entry that dominates any earlier write for that field, so
+//! concurrent edits from two tabs sharing the same `commits/` history stay
+//! convergent.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+/// Causal ordering stamp: `version` is a per-record Lamport clock, `actor_id`
+/// breaks ties between concurrent writers deterministically.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LamportStamp {
+    pub version: u64,
+    pub actor_id: String,
+}
+
+impl PartialOrd for LamportStamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LamportStamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.version
+            .cmp(&other.version)
+            .then_with(|| self.actor_id.cmp(&other.actor_id))
+    }
+}
+
+/// A single change to one top-level JSON field of a record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FieldChange {
+    /// Last-writer-wins overwrite of a scalar (or whole-value) field.
+    Set(Value),
+    /// Observed-remove add of an array member.
+    Add(Value),
+    /// Observed-remove removal of a previously-added array member.
+    Remove(Value),
+    /// Tombstone dominating any earlier op for this field.
+    Tombstone,
+}
+
+/// One entry in a record's causal merge log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeOp {
+    pub stamp: LamportStamp,
+    pub field: String,
+    pub change: FieldChange,
+}
+
+/// The append-only causal log backing a single `(collection, rkey)`.
+///
+/// `Update` appends an op rather than overwriting; reads replay the log (or
+/// a cached snapshot plus tail, in a future revision) to produce the
+/// materialized value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeLog {
+    ops: Vec<MergeOp>,
+}
+
+impl MergeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an op to the log. Ops may arrive out of stamp order (e.g. a
+    /// delayed sync from another tab); `materialize` sorts before folding.
+    pub fn append(&mut self, op: MergeOp) {
+        self.ops.push(op);
+    }
+
+    /// Number of ops in the log so far, usable as a per-record monotonic
+    /// counter for the next write's `LamportStamp::version`.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Diff two JSON objects field-by-field and append an op for every
+    /// field that changed, so callers don't have to hand-build ops for a
+    /// plain whole-value `Update`. A field that's an array on both sides
+    /// is diffed by membership (`Add`/`Remove` per element) so concurrent
+    /// array edits from two actors converge via observed-remove semantics
+    /// instead of one whole-array `Set` clobbering the other; every other
+    /// field is a last-writer-wins `Set`.
+    pub fn append_diff(&mut self, stamp: &LamportStamp, old: &Value, new: &Value) {
+        let (Value::Object(old_map), Value::Object(new_map)) = (old, new) else {
+            self.append(MergeOp {
+                stamp: stamp.clone(),
+                field: String::new(),
+                change: FieldChange::Set(new.clone()),
+            });
+            return;
+        };
+
+        for (field, value) in new_map {
+            let old_value = old_map.get(field);
+            if old_value == Some(value) {
+                continue;
+            }
+            match (old_value, value) {
+                (Some(Value::Array(old_items)), Value::Array(new_items)) => {
+                    for item in new_items {
+                        if !old_items.contains(item) {
+                            self.append(MergeOp {
+                                stamp: stamp.clone(),
+                                field: field.clone(),
+                                change: FieldChange::Add(item.clone()),
+                            });
+                        }
+                    }
+                    for item in old_items {
+                        if !new_items.contains(item) {
+                            self.append(MergeOp {
+                                stamp: stamp.clone(),
+                                field: field.clone(),
+                                change: FieldChange::Remove(item.clone()),
+                            });
+                        }
+                    }
+                }
+                (None, Value::Array(new_items)) => {
+                    for item in new_items {
+                        self.append(MergeOp {
+                            stamp: stamp.clone(),
+                            field: field.clone(),
+                            change: FieldChange::Add(item.clone()),
+                        });
+                    }
+                }
+                _ => {
+                    self.append(MergeOp {
+                        stamp: stamp.clone(),
+                        field: field.clone(),
+                        change: FieldChange::Set(value.clone()),
+                    });
+                }
+            }
+        }
+        for field in old_map.keys() {
+            if !new_map.contains_key(field) {
+                self.append(MergeOp {
+                    stamp: stamp.clone(),
+                    field: field.clone(),
+                    change: FieldChange::Tombstone,
+                });
+            }
+        }
+    }
+
+    /// Fold the log in stamp order into a materialized JSON object.
+    pub fn materialize(&self) -> Value {
+        let mut ordered: Vec<&MergeOp> = self.ops.iter().collect();
+        ordered.sort_by(|a, b| a.stamp.cmp(&b.stamp));
+
+        let mut fields: BTreeMap<String, Value> = BTreeMap::new();
+        let mut tombstoned: HashSet<String> = HashSet::new();
+
+        for op in ordered {
+            match &op.change {
+                FieldChange::Tombstone => {
+                    tombstoned.insert(op.field.clone());
+                    fields.remove(&op.field);
+                }
+                FieldChange::Set(value) => {
+                    tombstoned.remove(&op.field);
+                    fields.insert(op.field.clone(), value.clone());
+                }
+                FieldChange::Add(member) => {
+                    tombstoned.remove(&op.field);
+                    let entry = fields
+                        .entry(op.field.clone())
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(items) = entry {
+                        if !items.contains(member) {
+                            items.push(member.clone());
+                        }
+                    }
+                }
+                FieldChange::Remove(member) => {
+                    if let Some(Value::Array(items)) = fields.get_mut(&op.field) {
+                        items.retain(|item| item != member);
+                    }
+                }
+            }
+        }
+
+        Value::Object(fields.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp(version: u64, actor: &str) -> LamportStamp {
+        LamportStamp {
+            version,
+            actor_id: actor.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lww_on_concurrent_scalar_edit() {
+        let mut log = MergeLog::new();
+        log.append(MergeOp {
+            stamp: stamp(1, "tab-a"),
+            field: "text".to_string(),
+            change: FieldChange::Set(Value::String("hello".to_string())),
+        });
+        log.append(MergeOp {
+            stamp: stamp(2, "tab-b"),
+            field: "text".to_string(),
+            change: FieldChange::Set(Value::String("hello world".to_string())),
+        });
+
+        let merged = log.materialize();
+        assert_eq!(merged["text"], "hello world");
+    }
+
+    #[test]
+    fn test_tombstone_dominates_earlier_set() {
+        let mut log = MergeLog::new();
+        log.append(MergeOp {
+            stamp: stamp(1, "tab-a"),
+            field: "avatar".to_string(),
+            change: FieldChange::Set(Value::String("a.png".to_string())),
+        });
+        log.append(MergeOp {
+            stamp: stamp(2, "tab-a"),
+            field: "avatar".to_string(),
+            change: FieldChange::Tombstone,
+        });
+
+        let merged = log.materialize();
+        assert!(merged.get("avatar").is_none());
+    }
+
+    #[test]
+    fn test_or_set_add_remove_on_array_field() {
+        let mut log = MergeLog::new();
+        log.append(MergeOp {
+            stamp: stamp(1, "tab-a"),
+            field: "tags".to_string(),
+            change: FieldChange::Add(Value::String("rust".to_string())),
+        });
+        log.append(MergeOp {
+            stamp: stamp(2, "tab-b"),
+            field: "tags".to_string(),
+            change: FieldChange::Add(Value::String("wasm".to_string())),
+        });
+        log.append(MergeOp {
+            stamp: stamp(3, "tab-a"),
+            field: "tags".to_string(),
+            change: FieldChange::Remove(Value::String("rust".to_string())),
+        });
+
+        let merged = log.materialize();
+        assert_eq!(merged["tags"], serde_json::json!(["wasm"]));
+    }
+
+    #[test]
+    fn test_append_diff_only_changed_fields() {
+        let mut log = MergeLog::new();
+        let old = serde_json::json!({"text": "a", "likes": 1});
+        let new = serde_json::json!({"text": "b", "likes": 1});
+        log.append_diff(&stamp(1, "tab-a"), &old, &new);
+
+        let merged = log.materialize();
+        assert_eq!(merged["text"], "b");
+        assert!(merged.get("likes").is_none());
+    }
+
+    #[test]
+    fn test_append_diff_emits_add_remove_for_array_fields() {
+        let mut log = MergeLog::new();
+        let old = serde_json::json!({"tags": ["rust"]});
+        let new = serde_json::json!({"tags": ["wasm"]});
+        log.append_diff(&stamp(1, "tab-a"), &old, &new);
+
+        let merged = log.materialize();
+        assert_eq!(merged["tags"], serde_json::json!(["wasm"]));
+    }
+
+    #[test]
+    fn test_append_diff_array_adds_converge_across_actors() {
+        let mut log = MergeLog::new();
+        let base = serde_json::json!({"tags": []});
+        let from_a = serde_json::json!({"tags": ["rust"]});
+        let from_b = serde_json::json!({"tags": ["wasm"]});
+
+        log.append_diff(&stamp(1, "tab-a"), &base, &from_a);
+        log.append_diff(&stamp(2, "tab-b"), &base, &from_b);
+
+        let merged = log.materialize();
+        let tags = merged["tags"].as_array().unwrap();
+        assert!(tags.contains(&serde_json::json!("rust")));
+        assert!(tags.contains(&serde_json::json!("wasm")));
+    }
+}
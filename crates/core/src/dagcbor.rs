@@ -0,0 +1,235 @@
+//! Minimal canonical DAG-CBOR encoder/decoder (RFC 8949 core deterministic
+//! encoding) for the JSON value subset the repository needs to hash: null,
+//! bools, integers, floats, UTF-8 strings, arrays, and string-keyed maps.
+//! Map keys are sorted by their own encoded bytes, per the canonical
+//! ordering rule, so two equal value sets always produce identical bytes —
+//! a prerequisite for CIDs that are stable across re-encodes.
+
+use crate::error::{Error, Result};
+use serde_json::Value;
+
+/// Encode `value` as canonical DAG-CBOR bytes.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encode_into(value, &mut buf);
+    buf
+}
+
+/// Decode bytes produced by [`encode`] back into a [`Value`], for the same
+/// subset `encode` covers.
+pub fn decode(bytes: &[u8]) -> Result<Value> {
+    let mut pos = 0usize;
+    decode_value(bytes, &mut pos)
+}
+
+fn read_u8(bytes: &[u8], pos: &mut usize) -> Result<u8> {
+    let byte = *bytes
+        .get(*pos)
+        .ok_or_else(|| Error::SerializationError("Truncated DAG-CBOR".to_string()))?;
+    *pos += 1;
+    Ok(byte)
+}
+
+fn read_len(bytes: &[u8], pos: &mut usize, info: u8) -> Result<u64> {
+    match info {
+        0..=23 => Ok(info as u64),
+        24 => Ok(read_u8(bytes, pos)? as u64),
+        25 => {
+            let slice = bytes
+                .get(*pos..*pos + 2)
+                .ok_or_else(|| Error::SerializationError("Truncated DAG-CBOR length".to_string()))?;
+            *pos += 2;
+            Ok(u16::from_be_bytes([slice[0], slice[1]]) as u64)
+        }
+        26 => {
+            let slice = bytes
+                .get(*pos..*pos + 4)
+                .ok_or_else(|| Error::SerializationError("Truncated DAG-CBOR length".to_string()))?;
+            *pos += 4;
+            Ok(u32::from_be_bytes(slice.try_into().unwrap()) as u64)
+        }
+        27 => {
+            let slice = bytes
+                .get(*pos..*pos + 8)
+                .ok_or_else(|| Error::SerializationError("Truncated DAG-CBOR length".to_string()))?;
+            *pos += 8;
+            Ok(u64::from_be_bytes(slice.try_into().unwrap()))
+        }
+        _ => Err(Error::SerializationError(format!(
+            "Unsupported DAG-CBOR length encoding: {}",
+            info
+        ))),
+    }
+}
+
+fn decode_value(bytes: &[u8], pos: &mut usize) -> Result<Value> {
+    let head = read_u8(bytes, pos)?;
+    let major = head >> 5;
+    let info = head & 0x1f;
+
+    match major {
+        0 => Ok(Value::from(read_len(bytes, pos, info)?)),
+        1 => Ok(Value::from(-1 - read_len(bytes, pos, info)? as i64)),
+        3 => {
+            let len = read_len(bytes, pos, info)? as usize;
+            let slice = bytes
+                .get(*pos..*pos + len)
+                .ok_or_else(|| Error::SerializationError("Truncated DAG-CBOR string".to_string()))?;
+            *pos += len;
+            let s = String::from_utf8(slice.to_vec())
+                .map_err(|e| Error::SerializationError(e.to_string()))?;
+            Ok(Value::String(s))
+        }
+        4 => {
+            let len = read_len(bytes, pos, info)? as usize;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                items.push(decode_value(bytes, pos)?);
+            }
+            Ok(Value::Array(items))
+        }
+        5 => {
+            let len = read_len(bytes, pos, info)? as usize;
+            let mut map = serde_json::Map::new();
+            for _ in 0..len {
+                let key = decode_value(bytes, pos)?;
+                let key = key
+                    .as_str()
+                    .ok_or_else(|| Error::SerializationError("Non-string DAG-CBOR map key".to_string()))?
+                    .to_string();
+                let value = decode_value(bytes, pos)?;
+                map.insert(key, value);
+            }
+            Ok(Value::Object(map))
+        }
+        7 => match info {
+            20 => Ok(Value::Bool(false)),
+            21 => Ok(Value::Bool(true)),
+            22 => Ok(Value::Null),
+            27 => {
+                let slice = bytes
+                    .get(*pos..*pos + 8)
+                    .ok_or_else(|| Error::SerializationError("Truncated DAG-CBOR float".to_string()))?;
+                *pos += 8;
+                Ok(serde_json::json!(f64::from_be_bytes(
+                    slice.try_into().unwrap()
+                )))
+            }
+            _ => Err(Error::SerializationError(format!(
+                "Unsupported DAG-CBOR simple value: {}",
+                info
+            ))),
+        },
+        _ => Err(Error::SerializationError(format!(
+            "Unsupported DAG-CBOR major type: {}",
+            major
+        ))),
+    }
+}
+
+fn encode_into(value: &Value, buf: &mut Vec<u8>) {
+    match value {
+        Value::Null => buf.push(0xf6),
+        Value::Bool(false) => buf.push(0xf4),
+        Value::Bool(true) => buf.push(0xf5),
+        Value::Number(n) => encode_number(n, buf),
+        Value::String(s) => {
+            encode_head(3, s.len() as u64, buf);
+            buf.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(items) => {
+            encode_head(4, items.len() as u64, buf);
+            for item in items {
+                encode_into(item, buf);
+            }
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(Vec<u8>, &Value)> = map
+                .iter()
+                .map(|(k, v)| {
+                    let mut key_buf = Vec::new();
+                    encode_head(3, k.len() as u64, &mut key_buf);
+                    key_buf.extend_from_slice(k.as_bytes());
+                    (key_buf, v)
+                })
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+            encode_head(5, entries.len() as u64, buf);
+            for (key_bytes, val) in entries {
+                buf.extend_from_slice(&key_bytes);
+                encode_into(val, buf);
+            }
+        }
+    }
+}
+
+fn encode_number(n: &serde_json::Number, buf: &mut Vec<u8>) {
+    if let Some(u) = n.as_u64() {
+        encode_head(0, u, buf);
+    } else if let Some(i) = n.as_i64() {
+        encode_head(1, (-1 - i) as u64, buf);
+    } else if let Some(f) = n.as_f64() {
+        buf.push(0xfb);
+        buf.extend_from_slice(&f.to_be_bytes());
+    }
+}
+
+/// Write a CBOR major-type header with the shortest length encoding that
+/// fits `len` (the canonical encoding rule). `pub(crate)` so callers
+/// assembling CBOR by hand outside a [`Value`] (e.g. [`crate::car`]'s CID
+/// links) can reuse the same length-encoding rule instead of duplicating
+/// it.
+pub(crate) fn encode_head(major: u8, len: u64, buf: &mut Vec<u8>) {
+    let major = major << 5;
+    if len < 24 {
+        buf.push(major | len as u8);
+    } else if len <= u8::MAX as u64 {
+        buf.push(major | 24);
+        buf.push(len as u8);
+    } else if len <= u16::MAX as u64 {
+        buf.push(major | 25);
+        buf.extend_from_slice(&(len as u16).to_be_bytes());
+    } else if len <= u32::MAX as u64 {
+        buf.push(major | 26);
+        buf.extend_from_slice(&(len as u32).to_be_bytes());
+    } else {
+        buf.push(major | 27);
+        buf.extend_from_slice(&len.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_encoding() {
+        assert_eq!(encode(&Value::Null), vec![0xf6]);
+        assert_eq!(encode(&Value::Bool(true)), vec![0xf5]);
+        assert_eq!(encode(&serde_json::json!(5)), vec![0x05]);
+        assert_eq!(encode(&serde_json::json!(-1)), vec![0x20]);
+    }
+
+    #[test]
+    fn test_string_encoding() {
+        assert_eq!(encode(&serde_json::json!("a")), vec![0x61, b'a']);
+    }
+
+    #[test]
+    fn test_map_keys_are_sorted_canonically() {
+        let a = serde_json::json!({"b": 1, "a": 2});
+        let b = serde_json::json!({"a": 2, "b": 1});
+        assert_eq!(encode(&a), encode(&b));
+    }
+
+    #[test]
+    fn test_decode_round_trip() {
+        let value = serde_json::json!({
+            "l": serde_json::Value::Null,
+            "e": [{"p": 3, "k": "post/1", "v": "bafyrei1", "t": serde_json::Value::Null}],
+        });
+        let bytes = encode(&value);
+        assert_eq!(decode(&bytes).unwrap(), value);
+    }
+}
@@ -0,0 +1,129 @@
+//! Spec-compliant `did:key` encoding: a multibase string (`z` prefix for
+//! base58btc) wrapping an unsigned-varint multicodec prefix concatenated
+//! with the raw public key bytes, per the `did:key` method spec.
+
+use crate::error::{Error, Result};
+
+/// Signature curve a `did:key` identifies, recovered from its multicodec
+/// prefix so callers (e.g. `Crypto::verify`) can pick the matching verifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    Ed25519,
+    Secp256k1,
+}
+
+impl KeyType {
+    /// The two-byte unsigned-varint encoding of this curve's multicodec
+    /// code: `0xed01` for Ed25519, `0xe701` for secp256k1.
+    fn multicodec_prefix(self) -> [u8; 2] {
+        match self {
+            KeyType::Ed25519 => [0xed, 0x01],
+            KeyType::Secp256k1 => [0xe7, 0x01],
+        }
+    }
+
+    /// The expected raw public key length for this curve: 32 bytes for
+    /// Ed25519, 33 for secp256k1 (compressed SEC1 point).
+    fn key_len(self) -> usize {
+        match self {
+            KeyType::Ed25519 => 32,
+            KeyType::Secp256k1 => 33,
+        }
+    }
+}
+
+/// Encode `public_key` as a `did:key:z...` string: multicodec prefix for
+/// `key_type` concatenated with the raw key, base58btc-encoded and
+/// prefixed with the `z` multibase marker.
+pub fn bytes_to_did(key_type: KeyType, public_key: &[u8]) -> Result<String> {
+    if public_key.len() != key_type.key_len() {
+        return Err(Error::InvalidDid(format!(
+            "Expected a {}-byte key for {:?}, got {}",
+            key_type.key_len(),
+            key_type,
+            public_key.len()
+        )));
+    }
+
+    let mut payload = Vec::with_capacity(2 + public_key.len());
+    payload.extend_from_slice(&key_type.multicodec_prefix());
+    payload.extend_from_slice(public_key);
+
+    Ok(format!("did:key:z{}", bs58::encode(&payload).into_string()))
+}
+
+/// Decode a `did:key:z...` string back into its curve and raw public key
+/// bytes, rejecting payloads whose length doesn't match the codec.
+pub fn did_to_bytes(did: &str) -> Result<(KeyType, Vec<u8>)> {
+    let multibase = did
+        .strip_prefix("did:key:")
+        .ok_or_else(|| Error::InvalidDid(format!("Not a did:key: {}", did)))?;
+
+    let encoded = multibase
+        .strip_prefix('z')
+        .ok_or_else(|| Error::InvalidDid(format!("Unsupported did:key multibase: {}", did)))?;
+
+    let payload = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| Error::InvalidDid(format!("Invalid base58btc in did:key: {}", e)))?;
+
+    let (key_type, prefix_len) = match payload.as_slice() {
+        [0xed, 0x01, ..] => (KeyType::Ed25519, 2),
+        [0xe7, 0x01, ..] => (KeyType::Secp256k1, 2),
+        _ => {
+            return Err(Error::InvalidDid(format!(
+                "Unrecognized did:key multicodec prefix in {}",
+                did
+            )))
+        }
+    };
+
+    let key = payload[prefix_len..].to_vec();
+    if key.len() != key_type.key_len() {
+        return Err(Error::InvalidDid(format!(
+            "did:key payload has {} key bytes, expected {} for {:?}",
+            key.len(),
+            key_type.key_len(),
+            key_type
+        )));
+    }
+
+    Ok((key_type, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_round_trips() {
+        let key = [7u8; 32];
+        let did = bytes_to_did(KeyType::Ed25519, &key).unwrap();
+        assert!(did.starts_with("did:key:z"));
+
+        let (key_type, decoded) = did_to_bytes(&did).unwrap();
+        assert_eq!(key_type, KeyType::Ed25519);
+        assert_eq!(decoded, key);
+    }
+
+    #[test]
+    fn test_secp256k1_round_trips() {
+        let key = [3u8; 33];
+        let did = bytes_to_did(KeyType::Secp256k1, &key).unwrap();
+
+        let (key_type, decoded) = did_to_bytes(&did).unwrap();
+        assert_eq!(key_type, KeyType::Secp256k1);
+        assert_eq!(decoded, key);
+    }
+
+    #[test]
+    fn test_rejects_wrong_key_length() {
+        assert!(bytes_to_did(KeyType::Ed25519, &[1u8; 16]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_did() {
+        assert!(did_to_bytes("did:key:znotbase58!!!").is_err());
+        assert!(did_to_bytes("did:plc:abc").is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! DID document resolution for `did:web` and `did:plc` identifiers.
+//!
+//! `did:key` embeds its verifying key in the identifier itself, but
+//! `did:web`/`did:plc` DIDs don't — their key material lives in a DID
+//! document fetched over HTTP, so verifying a commit signed by one of these
+//! accounts (e.g. during federation) requires resolving that document first.
+
+use crate::did_key::{self, KeyType};
+use crate::error::{Error, Result};
+use crate::traits::{Clock, KvStore};
+use crate::types::Did;
+use serde::{Deserialize, Serialize};
+
+/// Pluggable transport for fetching a DID document's bytes, so resolution
+/// isn't hard-wired to one HTTP client (mirrors `PdsClient` in `repo.rs`).
+#[async_trait::async_trait(?Send)]
+pub trait DidDocumentFetcher {
+    /// Fetch the raw bytes served at `url`.
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    #[serde(rename = "publicKeyMultibase")]
+    pub public_key_multibase: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DidDocument {
+    pub id: String,
+    #[serde(rename = "verificationMethod", default)]
+    pub verification_method: Vec<VerificationMethod>,
+}
+
+impl DidDocument {
+    /// Decode each `verificationMethod`'s `publicKeyMultibase` the same way
+    /// a `did:key` is decoded, yielding `(key id, curve, raw key bytes)`
+    /// triples to try a signature against.
+    pub fn verification_keys(&self) -> Result<Vec<(String, KeyType, Vec<u8>)>> {
+        self.verification_method
+            .iter()
+            .filter_map(|vm| {
+                vm.public_key_multibase
+                    .as_ref()
+                    .map(|multibase| (vm.id.clone(), multibase))
+            })
+            .map(|(id, multibase)| {
+                let (key_type, key) = decode_multibase_key(multibase)?;
+                Ok((id, key_type, key))
+            })
+            .collect()
+    }
+}
+
+/// `publicKeyMultibase` uses the same multicodec-prefixed, base58btc-encoded
+/// shape as a `did:key`, just without the `did:key:` wrapper.
+fn decode_multibase_key(multibase: &str) -> Result<(KeyType, Vec<u8>)> {
+    did_key::did_to_bytes(&format!("did:key:{}", multibase))
+}
+
+fn resolve_url(did: &Did) -> Result<String> {
+    let s = did.as_str();
+    if let Some(domain_and_path) = s.strip_prefix("did:web:") {
+        // did:web path-encodes ':' as '/' for DIDs that resolve beneath a
+        // domain's root, e.g. did:web:example.com:user:alice.
+        let host_path = domain_and_path.replace(':', "/");
+        Ok(format!("https://{}/.well-known/did.json", host_path))
+    } else if s.starts_with("did:plc:") {
+        Ok(format!("https://plc.directory/{}", s))
+    } else {
+        Err(Error::InvalidDid(format!(
+            "Don't know how to resolve {} — only did:web and did:plc are supported",
+            s
+        )))
+    }
+}
+
+/// Resolve `did`'s DID document over HTTP via `fetcher`, with no caching.
+pub async fn resolve(did: &Did, fetcher: &dyn DidDocumentFetcher) -> Result<DidDocument> {
+    let url = resolve_url(did)?;
+    let bytes = fetcher.fetch(&url).await?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        Error::InvalidDid(format!(
+            "Malformed DID document for {}: {}",
+            did.as_str(),
+            e
+        ))
+    })
+}
+
+const CACHE_TTL_SECS: i64 = 3600;
+
+#[derive(Serialize, Deserialize)]
+struct CachedDocument {
+    document: DidDocument,
+    cached_at: i64,
+}
+
+fn cache_key(did: &Did) -> String {
+    format!("did_resolver:{}", did.as_str())
+}
+
+async fn resolve_cached(
+    did: &Did,
+    fetcher: &dyn DidDocumentFetcher,
+    cache: &mut dyn KvStore,
+    clock: &dyn Clock,
+) -> Result<DidDocument> {
+    let key = cache_key(did);
+    if let Some(bytes) = cache.get(&key)? {
+        if let Ok(cached) = serde_json::from_slice::<CachedDocument>(&bytes) {
+            if clock.now().timestamp() - cached.cached_at < CACHE_TTL_SECS {
+                return Ok(cached.document);
+            }
+        }
+    }
+
+    let document = resolve(did, fetcher).await?;
+    let cached = CachedDocument {
+        document: document.clone(),
+        cached_at: clock.now().timestamp(),
+    };
+    cache.put(&key, &serde_json::to_vec(&cached)?)?;
+    Ok(document)
+}
+
+/// Resolve `did` (from `cache` when still fresh per `clock`'s TTL, else via
+/// `fetcher`), then try each of its verification-method keys against
+/// `data`/`signature`, succeeding if any one of them verifies. This is the
+/// `did:web`/`did:plc` counterpart to `traits::verify_by_did`, which only
+/// handles `did:key`.
+pub async fn verify_with_did(
+    data: &[u8],
+    signature: &[u8],
+    did: &Did,
+    fetcher: &dyn DidDocumentFetcher,
+    cache: &mut dyn KvStore,
+    clock: &dyn Clock,
+) -> Result<bool> {
+    let document = resolve_cached(did, fetcher, cache, clock).await?;
+
+    for (_key_id, key_type, key_bytes) in document.verification_keys()? {
+        let candidate_did = did_key::bytes_to_did(key_type, &key_bytes)?;
+        if crate::traits::verify_by_did(data, signature, &candidate_did)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{Ed25519Crypto, MemoryKvStore, SystemClock};
+
+    struct FakeFetcher {
+        url: String,
+        body: Vec<u8>,
+        calls: std::cell::RefCell<u32>,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl DidDocumentFetcher for FakeFetcher {
+        async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+            *self.calls.borrow_mut() += 1;
+            if url == self.url {
+                Ok(self.body.clone())
+            } else {
+                Err(Error::NotFound(format!("no fixture for {}", url)))
+            }
+        }
+    }
+
+    fn document_for(did: &str, key_id: &str, public_key: &[u8]) -> Vec<u8> {
+        let multibase = did_key::bytes_to_did(KeyType::Ed25519, public_key)
+            .unwrap()
+            .strip_prefix("did:key:")
+            .unwrap()
+            .to_string();
+        serde_json::to_vec(&serde_json::json!({
+            "id": did,
+            "verificationMethod": [{
+                "id": key_id,
+                "type": "Multikey",
+                "controller": did,
+                "publicKeyMultibase": multibase,
+            }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_url_for_did_web() {
+        let did = Did::new("did:web:example.com").unwrap();
+        assert_eq!(
+            resolve_url(&did).unwrap(),
+            "https://example.com/.well-known/did.json"
+        );
+    }
+
+    #[test]
+    fn test_resolve_url_for_did_plc() {
+        let did = Did::new("did:plc:abc123").unwrap();
+        assert_eq!(resolve_url(&did).unwrap(), "https://plc.directory/did:plc:abc123");
+    }
+
+    #[test]
+    fn test_resolve_url_rejects_did_key() {
+        let did = Did::new("did:key:zSomeKey").unwrap();
+        assert!(resolve_url(&did).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_did_checks_resolved_key() {
+        let signer = Ed25519Crypto::new();
+        let did = Did::new("did:web:example.com").unwrap();
+        let fetcher = FakeFetcher {
+            url: resolve_url(&did).unwrap(),
+            body: document_for(did.as_str(), "#key-1", &signer.public_key()),
+            calls: std::cell::RefCell::new(0),
+        };
+        let mut cache = MemoryKvStore::new();
+        let clock = SystemClock;
+
+        let data = b"a commit worth signing";
+        let signature = signer.sign(data).unwrap();
+
+        let verified = verify_with_did(data, &signature, &did, &fetcher, &mut cache, &clock)
+            .await
+            .unwrap();
+        assert!(verified);
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_did_caches_document() {
+        let signer = Ed25519Crypto::new();
+        let did = Did::new("did:web:example.com").unwrap();
+        let fetcher = FakeFetcher {
+            url: resolve_url(&did).unwrap(),
+            body: document_for(did.as_str(), "#key-1", &signer.public_key()),
+            calls: std::cell::RefCell::new(0),
+        };
+        let mut cache = MemoryKvStore::new();
+        let clock = SystemClock;
+
+        let data = b"a commit worth signing";
+        let signature = signer.sign(data).unwrap();
+
+        verify_with_did(data, &signature, &did, &fetcher, &mut cache, &clock)
+            .await
+            .unwrap();
+        verify_with_did(data, &signature, &did, &fetcher, &mut cache, &clock)
+            .await
+            .unwrap();
+
+        assert_eq!(*fetcher.calls.borrow(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_did_rejects_wrong_signature() {
+        let signer = Ed25519Crypto::new();
+        let other = Ed25519Crypto::new();
+        let did = Did::new("did:web:example.com").unwrap();
+        let fetcher = FakeFetcher {
+            url: resolve_url(&did).unwrap(),
+            body: document_for(did.as_str(), "#key-1", &signer.public_key()),
+            calls: std::cell::RefCell::new(0),
+        };
+        let mut cache = MemoryKvStore::new();
+        let clock = SystemClock;
+
+        let data = b"a commit worth signing";
+        let signature = other.sign(data).unwrap();
+
+        let verified = verify_with_did(data, &signature, &did, &fetcher, &mut cache, &clock)
+            .await
+            .unwrap();
+        assert!(!verified);
+    }
+}
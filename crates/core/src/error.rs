@@ -2,8 +2,17 @@
 
 use thiserror::Error;
 
-/// Core error type for PDS operations
+/// Core error type for PDS operations.
+///
+/// Most variants still carry a plain `String` for ad hoc, human-written
+/// context (e.g. "Truncated CAR frame") where there's no underlying error
+/// to preserve. The variants below that *do* wrap an external error keep
+/// it via `#[source]`/`#[from]` instead of stringifying it, so callers can
+/// walk the cause chain with [`std::error::Error::source`] or downcast to
+/// the original type. `#[non_exhaustive]` so new causes can be added
+/// without a breaking change to every `match` on this enum.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Invalid record: {0}")]
     InvalidRecord(String),
@@ -37,19 +46,353 @@ pub enum Error {
 
     #[error("Validation error: {0}")]
     ValidationError(String),
+
+    /// A JSON (de)serialization failure, with the original `serde_json`
+    /// error preserved rather than stringified — this is what `?` now
+    /// produces at every `serde_json::to_vec`/`from_slice` call site.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// An Automerge CRDT failure, with the original error preserved. Use
+    /// this (via `?`/`.into()`) for new call sites instead of
+    /// `AutomergeError(String)`, which remains for existing sites that
+    /// attach their own human-written context alongside the message.
+    #[error("Automerge failure: {0}")]
+    Automerge(#[from] automerge::AutomergeError),
+
+    /// An I/O failure from a storage backend or network transport.
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// A storage-backend failure whose underlying cause isn't one of the
+    /// above (e.g. an IndexedDB or SQLite driver error), preserved as a
+    /// boxed source so `KvStore` implementors aren't required to share a
+    /// common error type.
+    #[error("Storage backend error: {0}")]
+    StorageBackendError(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    /// A human-readable scope attached by [`ResultExt::context`], wrapping
+    /// the error it was attached to. `message` already embeds the wrapped
+    /// error's own `Display` output, so nesting several calls to
+    /// `.context(...)` as an error travels up through record validation →
+    /// commit build → MST/repo write → storage renders the full
+    /// breadcrumb chain (outermost scope first) ending in the root cause,
+    /// while `source()` still walks the original, unwrapped error chain.
+    #[error("{message}")]
+    Context {
+        message: String,
+        #[source]
+        source: Box<Error>,
+    },
 }
 
 /// Result type alias for PDS operations
 pub type Result<T> = std::result::Result<T, Error>;
 
-impl From<serde_json::Error> for Error {
-    fn from(err: serde_json::Error) -> Self {
-        Error::SerializationError(err.to_string())
+impl Error {
+    /// The stable, PascalCase XRPC error name ATProto clients key their
+    /// error handling on (the `error` field of an XRPC error response). A
+    /// [`Error::Context`] wrapper defers to whatever it wraps, since the
+    /// breadcrumb is only meant for logs, not the wire.
+    pub fn xrpc_name(&self) -> &'static str {
+        match self {
+            Error::Context { source, .. } => source.xrpc_name(),
+            Error::InvalidRecord(_) => "InvalidRecord",
+            Error::InvalidCommit(_) => "InvalidSwap",
+            Error::InvalidDid(_) => "InvalidRequest",
+            Error::InvalidCid(_) => "InvalidRequest",
+            Error::ValidationError(_) => "InvalidRequest",
+            Error::NotFound(_) => "RecordNotFound",
+            Error::RepositoryError(_) => "InternalServerError",
+            Error::AutomergeError(_) | Error::Automerge(_) => "InternalServerError",
+            Error::SerializationError(_) | Error::Json(_) => "InternalServerError",
+            Error::StorageError(_) | Error::StorageBackendError(_) | Error::IoError(_) => {
+                "InternalServerError"
+            }
+            Error::CryptoError(_) => "InternalServerError",
+        }
+    }
+
+    /// The HTTP status an XRPC handler should respond with: 400 for
+    /// validation-shaped failures, 404 when the requested thing doesn't
+    /// exist, 500 for everything internal (storage, Automerge, crypto). A
+    /// [`Error::Context`] wrapper defers to whatever it wraps.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Error::Context { source, .. } => source.http_status(),
+            Error::InvalidRecord(_)
+            | Error::InvalidCommit(_)
+            | Error::InvalidDid(_)
+            | Error::InvalidCid(_)
+            | Error::ValidationError(_) => 400,
+            Error::NotFound(_) => 404,
+            Error::RepositoryError(_)
+            | Error::AutomergeError(_)
+            | Error::Automerge(_)
+            | Error::SerializationError(_)
+            | Error::Json(_)
+            | Error::StorageError(_)
+            | Error::StorageBackendError(_)
+            | Error::IoError(_)
+            | Error::CryptoError(_) => 500,
+        }
+    }
+
+    /// Render this error as the XRPC error body ATProto clients expect:
+    /// `{"error": "<PascalCase name>", "message": "<human text>"}`.
+    pub fn to_xrpc_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": self.xrpc_name(),
+            "message": self.to_string(),
+        })
+    }
+
+    /// A low-cardinality, stable label for Prometheus-style counters — no
+    /// interpolated text, so it's safe to use directly as a label value. A
+    /// [`Error::Context`] wrapper defers to whatever it wraps.
+    pub fn metric_label(&self) -> &'static str {
+        match self {
+            Error::Context { source, .. } => source.metric_label(),
+            Error::InvalidRecord(_) => "invalid_record",
+            Error::InvalidCommit(_) => "invalid_commit",
+            Error::InvalidDid(_) => "invalid_did",
+            Error::InvalidCid(_) => "invalid_cid",
+            Error::ValidationError(_) => "validation",
+            Error::NotFound(_) => "not_found",
+            Error::RepositoryError(_) => "repository",
+            Error::AutomergeError(_) | Error::Automerge(_) => "automerge",
+            Error::SerializationError(_) | Error::Json(_) => "serialization",
+            Error::StorageError(_) | Error::StorageBackendError(_) => "storage",
+            Error::IoError(_) => "io",
+            Error::CryptoError(_) => "crypto",
+        }
+    }
+
+    /// Whether a caller should back off and retry instead of failing fast.
+    /// True only for the transient, infrastructure-shaped failures
+    /// (storage/repository I/O); false for anything caused by the data
+    /// itself being invalid, since retrying won't change that. A
+    /// [`Error::Context`] wrapper defers to whatever it wraps.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Context { source, .. } => source.is_retryable(),
+            Error::StorageError(_)
+            | Error::StorageBackendError(_)
+            | Error::IoError(_)
+            | Error::RepositoryError(_) => true,
+            Error::InvalidRecord(_)
+            | Error::InvalidCommit(_)
+            | Error::InvalidDid(_)
+            | Error::InvalidCid(_)
+            | Error::ValidationError(_)
+            | Error::NotFound(_)
+            | Error::AutomergeError(_)
+            | Error::Automerge(_)
+            | Error::SerializationError(_)
+            | Error::Json(_)
+            | Error::CryptoError(_) => false,
+        }
+    }
+
+    /// Whether this error was caused by the client (bad/missing input —
+    /// not worth alerting on) or is internal (storage, crypto, CRDT, I/O
+    /// failures — worth graphing and paging on). A [`Error::Context`]
+    /// wrapper defers to whatever it wraps.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Error::Context { source, .. } => source.severity(),
+            Error::InvalidRecord(_)
+            | Error::InvalidCommit(_)
+            | Error::InvalidDid(_)
+            | Error::InvalidCid(_)
+            | Error::ValidationError(_)
+            | Error::NotFound(_) => Severity::Client,
+            Error::RepositoryError(_)
+            | Error::AutomergeError(_)
+            | Error::Automerge(_)
+            | Error::SerializationError(_)
+            | Error::Json(_)
+            | Error::StorageError(_)
+            | Error::StorageBackendError(_)
+            | Error::IoError(_)
+            | Error::CryptoError(_) => Severity::Internal,
+        }
     }
 }
 
-impl From<automerge::AutomergeError> for Error {
-    fn from(err: automerge::AutomergeError) -> Self {
-        Error::AutomergeError(err.to_string())
+/// Coarse classification of an [`Error`] for observability and retry
+/// logic — see [`Error::severity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Caused by invalid or missing client input; expected in normal
+    /// operation and not worth alerting on.
+    Client,
+    /// An internal failure (storage, crypto, CRDT, I/O); worth graphing
+    /// and potentially paging on.
+    Internal,
+}
+
+/// Attach human-readable scope to an error as it crosses a repo/storage/
+/// crypto boundary, without losing the original cause.
+pub trait ResultExt<T> {
+    /// Wrap the error (if any) with `context`, eagerly formatted.
+    fn context<C: std::fmt::Display>(self, context: C) -> Result<T>;
+
+    /// Wrap the error (if any) with a lazily-computed context, so building
+    /// the message (e.g. formatting a CID) costs nothing on the success
+    /// path.
+    fn with_context<C: std::fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T>;
+}
+
+impl<T, E> ResultExt<T> for std::result::Result<T, E>
+where
+    E: Into<Error>,
+{
+    fn context<C: std::fmt::Display>(self, context: C) -> Result<T> {
+        self.map_err(|e| wrap_context(e.into(), context))
+    }
+
+    fn with_context<C: std::fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T> {
+        self.map_err(|e| wrap_context(e.into(), f()))
+    }
+}
+
+fn wrap_context<C: std::fmt::Display>(source: Error, context: C) -> Error {
+    let message = format!("{}: {}", context, source);
+    Error::Context {
+        message,
+        source: Box::new(source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_json_error_preserves_source() {
+        let parse_err = serde_json::from_str::<serde_json::Value>("not json").unwrap_err();
+        let err: Error = parse_err.into();
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_storage_backend_error_preserves_source() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::Other, "disk full");
+        let err = Error::StorageBackendError(Box::new(io_err));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_string_variants_have_no_source() {
+        let err = Error::InvalidRecord("bad record".to_string());
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn test_context_renders_breadcrumb_chain() {
+        let result: std::result::Result<(), Error> =
+            Err(Error::NotFound("commit abc123".to_string()));
+        let err = result
+            .context("loading commit for did:plc:alice")
+            .context("handling sync request")
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "handling sync request: loading commit for did:plc:alice: Not found: commit abc123"
+        );
+    }
+
+    #[test]
+    fn test_context_preserves_source_chain() {
+        let result: std::result::Result<(), Error> =
+            Err(Error::NotFound("commit abc123".to_string()));
+        let err = result.context("loading commit").unwrap_err();
+
+        let inner = err.source().expect("context wraps the original error");
+        assert_eq!(inner.to_string(), "Not found: commit abc123");
+    }
+
+    #[test]
+    fn test_with_context_is_lazy_on_success() {
+        let result: std::result::Result<u32, Error> = Ok(42);
+        let called = std::cell::Cell::new(false);
+        let value = result
+            .with_context(|| {
+                called.set(true);
+                "never evaluated"
+            })
+            .unwrap();
+
+        assert_eq!(value, 42);
+        assert!(!called.get());
+    }
+
+    #[test]
+    fn test_xrpc_mapping_for_validation_errors() {
+        let err = Error::InvalidRecord("missing field".to_string());
+        assert_eq!(err.xrpc_name(), "InvalidRecord");
+        assert_eq!(err.http_status(), 400);
+    }
+
+    #[test]
+    fn test_xrpc_mapping_for_not_found() {
+        let err = Error::NotFound("commit abc123".to_string());
+        assert_eq!(err.xrpc_name(), "RecordNotFound");
+        assert_eq!(err.http_status(), 404);
+    }
+
+    #[test]
+    fn test_xrpc_mapping_for_internal_errors() {
+        let err = Error::StorageError("disk unavailable".to_string());
+        assert_eq!(err.xrpc_name(), "InternalServerError");
+        assert_eq!(err.http_status(), 500);
+    }
+
+    #[test]
+    fn test_context_defers_xrpc_mapping_to_source() {
+        let result: std::result::Result<(), Error> =
+            Err(Error::NotFound("commit abc123".to_string()));
+        let err = result.context("loading commit").unwrap_err();
+
+        assert_eq!(err.xrpc_name(), "RecordNotFound");
+        assert_eq!(err.http_status(), 404);
+    }
+
+    #[test]
+    fn test_to_xrpc_json_shape() {
+        let err = Error::InvalidRecord("missing field".to_string());
+        let json = err.to_xrpc_json();
+        assert_eq!(json["error"], "InvalidRecord");
+        assert_eq!(json["message"], "Invalid record: missing field");
+    }
+
+    #[test]
+    fn test_storage_errors_are_retryable_and_internal() {
+        let err = Error::StorageError("connection reset".to_string());
+        assert_eq!(err.metric_label(), "storage");
+        assert!(err.is_retryable());
+        assert_eq!(err.severity(), Severity::Internal);
+    }
+
+    #[test]
+    fn test_validation_errors_are_not_retryable_and_client() {
+        let err = Error::ValidationError("missing rkey".to_string());
+        assert_eq!(err.metric_label(), "validation");
+        assert!(!err.is_retryable());
+        assert_eq!(err.severity(), Severity::Client);
+    }
+
+    #[test]
+    fn test_context_defers_classification_to_source() {
+        let result: std::result::Result<(), Error> =
+            Err(Error::StorageError("connection reset".to_string()));
+        let err = result.context("writing checkpoint").unwrap_err();
+
+        assert_eq!(err.metric_label(), "storage");
+        assert!(err.is_retryable());
+        assert_eq!(err.severity(), Severity::Internal);
     }
 }
@@ -12,8 +12,16 @@
 pub mod types;
 pub mod traits;
 pub mod repo;
+pub mod records;
 pub mod automerge_wrapper;
 pub mod snapshot;
+pub mod crdt;
+pub mod dagcbor;
+pub mod did_key;
+pub mod mst;
+pub mod car;
+pub mod ucan;
+pub mod did_resolver;
 pub mod error;
 
 pub use error::{Error, Result};
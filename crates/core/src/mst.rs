@@ -0,0 +1,568 @@
+//! Merkle Search Tree over record keys.
+//!
+//! For each record key (the `collection/rkey` path string) we compute
+//! SHA-256 of the key and count its leading zero bits, dividing by 2 (fan-out
+//! 4 — two bits of leading-zero-count select a layer) to get the key's
+//! layer; keys land on a layer deterministically. A node
+//! holds an ordered list of entries — key (prefix-compressed against the
+//! previous entry), value CID, and an optional pointer to the subtree
+//! covering keys between it and the next entry — plus an optional leftmost
+//! subtree pointer for keys before the first entry. Nodes are serialized as
+//! DAG-CBOR and hashed into a CID, so the root CID is a pure function of the
+//! `key -> value CID` set. Entry keys are written as text strings and CIDs
+//! as their base32 string form, not as byte strings and tag-42 CID links, so
+//! the root differs from the one another ATProto implementation computes
+//! for the same records.
+
+use crate::dagcbor;
+use crate::error::{Error, Result};
+use crate::types::{Cid, Record};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        if *byte == 0 {
+            bits += 8;
+            continue;
+        }
+        bits += byte.leading_zeros();
+        break;
+    }
+    bits
+}
+
+/// The layer a key deterministically lands on, given the tree's fan-out of
+/// 4 (two bits of leading-zero-count select one layer).
+pub fn layer_for_key(key: &str) -> u32 {
+    let hash = Sha256::digest(key.as_bytes());
+    leading_zero_bits(&hash) / 2
+}
+
+/// One leaf in an MST node.
+#[derive(Debug, Clone)]
+pub struct MstEntry {
+    pub key: String,
+    pub value_cid: Cid,
+    /// Subtree covering keys strictly between this entry and the next.
+    pub right: Option<Cid>,
+}
+
+/// A single MST node: an optional leftmost subtree (keys before the first
+/// entry) plus an ordered list of entries.
+#[derive(Debug, Clone, Default)]
+pub struct MstNode {
+    pub left: Option<Cid>,
+    pub entries: Vec<MstEntry>,
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.bytes().zip(b.bytes()).take_while(|(x, y)| x == y).count()
+}
+
+impl MstNode {
+    /// Canonical DAG-CBOR encoding used both to persist the node as a block
+    /// and to derive its CID; entry keys are prefix-compressed against the
+    /// previous entry in the node.
+    pub fn to_dag_cbor(&self) -> Vec<u8> {
+        let mut prev_key = String::new();
+        let entries_json: Vec<serde_json::Value> = self
+            .entries
+            .iter()
+            .map(|e| {
+                let shared = common_prefix_len(&prev_key, &e.key);
+                let suffix = e.key[shared..].to_string();
+                prev_key = e.key.clone();
+                serde_json::json!({
+                    "p": shared,
+                    "k": suffix,
+                    "v": e.value_cid.as_str(),
+                    "t": e.right.as_ref().map(|c| c.as_str()),
+                })
+            })
+            .collect();
+
+        let node = serde_json::json!({
+            "l": self.left.as_ref().map(|c| c.as_str()),
+            "e": entries_json,
+        });
+        dagcbor::encode(&node)
+    }
+
+    pub fn cid(&self) -> Cid {
+        Cid::from_bytes(&self.to_dag_cbor())
+    }
+
+    /// Decode a node from the DAG-CBOR bytes produced by
+    /// [`to_dag_cbor`](Self::to_dag_cbor), reconstructing each entry's full
+    /// key from its prefix-compressed suffix.
+    pub fn from_dag_cbor(bytes: &[u8]) -> Result<Self> {
+        let value = dagcbor::decode(bytes)?;
+        let obj = value
+            .as_object()
+            .ok_or_else(|| Error::SerializationError("MST node is not a map".to_string()))?;
+
+        let left = obj
+            .get("l")
+            .and_then(|v| v.as_str())
+            .map(Cid::from_string)
+            .transpose()?;
+
+        let entries_json = obj
+            .get("e")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| Error::SerializationError("MST node missing entries".to_string()))?;
+
+        let mut entries = Vec::with_capacity(entries_json.len());
+        let mut prev_key = String::new();
+        for entry in entries_json {
+            let shared = entry
+                .get("p")
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| Error::SerializationError("MST entry missing prefix len".to_string()))?
+                as usize;
+            let suffix = entry
+                .get("k")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| Error::SerializationError("MST entry missing key suffix".to_string()))?;
+            let value_cid = entry
+                .get("v")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| Error::SerializationError("MST entry missing value CID".to_string()))?;
+            let right = entry
+                .get("t")
+                .and_then(|v| v.as_str())
+                .map(Cid::from_string)
+                .transpose()?;
+
+            let key = format!("{}{}", &prev_key[..shared.min(prev_key.len())], suffix);
+            prev_key = key.clone();
+
+            entries.push(MstEntry {
+                key,
+                value_cid: Cid::from_string(value_cid)?,
+                right,
+            });
+        }
+
+        Ok(MstNode { left, entries })
+    }
+}
+
+/// Build an MST over `records` keyed by their `collection/rkey` path and
+/// return the root CID, or `None` for an empty record set.
+pub fn generate_mst(records: &[Record]) -> Result<Option<Cid>> {
+    let mut mst = Mst::new();
+    for record in records {
+        mst.insert(record.path(), record.cid()?);
+    }
+    Ok(mst.root())
+}
+
+/// Walk the node tree starting at `root`, looking each node up in `blocks`
+/// by CID, and return every leaf key in the tree. Returns an error if a
+/// referenced CID is missing from `blocks`, rather than silently skipping
+/// the subtree.
+pub fn collect_mst_keys(root: &Cid, blocks: &HashMap<Cid, Vec<u8>>) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+    collect_node_keys(root, blocks, &mut keys)?;
+    Ok(keys)
+}
+
+fn collect_node_keys(cid: &Cid, blocks: &HashMap<Cid, Vec<u8>>, keys: &mut Vec<String>) -> Result<()> {
+    let bytes = blocks
+        .get(cid)
+        .ok_or_else(|| Error::NotFound(format!("MST block not found: {}", cid)))?;
+    let node = MstNode::from_dag_cbor(bytes)?;
+
+    if let Some(left) = &node.left {
+        collect_node_keys(left, blocks, keys)?;
+    }
+    for entry in &node.entries {
+        keys.push(entry.key.clone());
+        if let Some(right) = &entry.right {
+            collect_node_keys(right, blocks, keys)?;
+        }
+    }
+    Ok(())
+}
+
+/// Walk the node tree at `root`, looking nodes up in `blocks` by CID, and
+/// return every leaf key mapped to its value CID — the same traversal as
+/// [`collect_mst_keys`] but keeping the value needed to tell a changed key
+/// from an unchanged one.
+fn collect_leaf_map(root: &Cid, blocks: &HashMap<Cid, Vec<u8>>) -> Result<BTreeMap<String, Cid>> {
+    let mut leaves = BTreeMap::new();
+    collect_node_leaves(root, blocks, &mut leaves)?;
+    Ok(leaves)
+}
+
+fn collect_node_leaves(
+    cid: &Cid,
+    blocks: &HashMap<Cid, Vec<u8>>,
+    leaves: &mut BTreeMap<String, Cid>,
+) -> Result<()> {
+    let bytes = blocks
+        .get(cid)
+        .ok_or_else(|| Error::NotFound(format!("MST block not found: {}", cid)))?;
+    let node = MstNode::from_dag_cbor(bytes)?;
+
+    if let Some(left) = &node.left {
+        collect_node_leaves(left, blocks, leaves)?;
+    }
+    for entry in &node.entries {
+        leaves.insert(entry.key.clone(), entry.value_cid.clone());
+        if let Some(right) = &entry.right {
+            collect_node_leaves(right, blocks, leaves)?;
+        }
+    }
+    Ok(())
+}
+
+/// Diff two MST roots (e.g. a commit's previous and new tree root) purely
+/// from their node blocks, without needing either side's full [`Mst`] kept
+/// in memory — the shape a firehose-style change-event generator actually
+/// has available (a root CID plus the CAR blocks for that commit). `None`
+/// is treated as the empty tree: with no old root every key of the new
+/// root is reported `added`, and with no new root every key of the old
+/// root is reported `removed`.
+pub fn diff_roots(
+    old_root: Option<&Cid>,
+    new_root: Option<&Cid>,
+    blocks: &HashMap<Cid, Vec<u8>>,
+) -> Result<MstDiff> {
+    let old_leaves = match old_root {
+        Some(cid) => collect_leaf_map(cid, blocks)?,
+        None => BTreeMap::new(),
+    };
+    let new_leaves = match new_root {
+        Some(cid) => collect_leaf_map(cid, blocks)?,
+        None => BTreeMap::new(),
+    };
+
+    let mut diff = MstDiff::default();
+    for (key, cid) in &new_leaves {
+        match old_leaves.get(key) {
+            None => diff.added.push(key.clone()),
+            Some(old_cid) if old_cid != cid => diff.changed.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    for key in old_leaves.keys() {
+        if !new_leaves.contains_key(key) {
+            diff.removed.push(key.clone());
+        }
+    }
+    Ok(diff)
+}
+
+/// The keys added, removed, and changed between two [`Mst`]s' leaf sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MstDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+/// A Merkle Search Tree over a repository's `collection/rkey -> value CID`
+/// key set. The node tree is rebuilt from the leaf set whenever a root is
+/// requested, which keeps insert/delete simple while still content
+/// addressing the same way a true incrementally-spliced tree would for an
+/// identical key set.
+#[derive(Debug, Clone, Default)]
+pub struct Mst {
+    leaves: BTreeMap<String, Cid>,
+}
+
+impl Mst {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: String, value_cid: Cid) {
+        self.leaves.insert(key, value_cid);
+    }
+
+    pub fn delete(&mut self, key: &str) {
+        self.leaves.remove(key);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Cid> {
+        self.leaves.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Recompute the node tree bottom-up and return its root CID, or `None`
+    /// for an empty tree.
+    pub fn root(&self) -> Option<Cid> {
+        self.root_with_blocks().map(|(cid, _)| cid)
+    }
+
+    /// Same as [`root`](Self::root), but also returns every node's
+    /// DAG-CBOR bytes keyed by its own CID, suitable for a CAR export.
+    pub fn root_with_blocks(&self) -> Option<(Cid, Vec<(Cid, Vec<u8>)>)> {
+        if self.leaves.is_empty() {
+            return None;
+        }
+        let layered: Vec<(String, Cid, u32)> = self
+            .leaves
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone(), layer_for_key(k)))
+            .collect();
+        let top_layer = layered.iter().map(|(_, _, l)| *l).max().unwrap_or(0);
+        let mut blocks = Vec::new();
+        let root = Self::build_layer(&layered, top_layer, &mut blocks);
+        Some((root, blocks))
+    }
+
+    /// Build the node covering all of `leaves` at `layer`, recursing into a
+    /// subtree one layer down for any run of entries whose own layer is
+    /// lower than `layer`, and record each constructed node's bytes in
+    /// `blocks` as it's built.
+    fn build_layer(leaves: &[(String, Cid, u32)], layer: u32, blocks: &mut Vec<(Cid, Vec<u8>)>) -> Cid {
+        let mut node = MstNode::default();
+        let mut gap: Vec<(String, Cid, u32)> = Vec::new();
+
+        for (key, cid, key_layer) in leaves {
+            if *key_layer == layer {
+                let subtree = Self::flush_gap(&mut gap, layer, blocks);
+                match node.entries.last_mut() {
+                    Some(last) => last.right = subtree,
+                    None => node.left = subtree,
+                }
+                node.entries.push(MstEntry {
+                    key: key.clone(),
+                    value_cid: cid.clone(),
+                    right: None,
+                });
+            } else {
+                gap.push((key.clone(), cid.clone(), *key_layer));
+            }
+        }
+
+        let trailing = Self::flush_gap(&mut gap, layer, blocks);
+        match node.entries.last_mut() {
+            Some(last) => last.right = trailing,
+            None => node.left = trailing,
+        }
+
+        let bytes = node.to_dag_cbor();
+        let cid = node.cid();
+        blocks.push((cid.clone(), bytes));
+        cid
+    }
+
+    /// Diff this tree's leaf set against `other`'s: keys only in `other` are
+    /// `added`, keys only in `self` are `removed`, and keys present in both
+    /// with a different value CID are `changed`.
+    pub fn diff(&self, other: &Mst) -> MstDiff {
+        let mut diff = MstDiff::default();
+
+        for (key, cid) in &other.leaves {
+            match self.leaves.get(key) {
+                None => diff.added.push(key.clone()),
+                Some(old_cid) if old_cid != cid => diff.changed.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        for key in self.leaves.keys() {
+            if !other.leaves.contains_key(key) {
+                diff.removed.push(key.clone());
+            }
+        }
+
+        diff
+    }
+
+    fn flush_gap(
+        gap: &mut Vec<(String, Cid, u32)>,
+        layer: u32,
+        blocks: &mut Vec<(Cid, Vec<u8>)>,
+    ) -> Option<Cid> {
+        if gap.is_empty() || layer == 0 {
+            return None;
+        }
+        let taken = std::mem::take(gap);
+        Some(Self::build_layer(&taken, layer - 1, blocks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cid_for(s: &str) -> Cid {
+        Cid::from_bytes(s.as_bytes())
+    }
+
+    #[test]
+    fn test_empty_tree_has_no_root() {
+        let mst = Mst::new();
+        assert!(mst.root().is_none());
+    }
+
+    #[test]
+    fn test_root_is_deterministic_for_same_key_set() {
+        let mut a = Mst::new();
+        a.insert("app.bsky.feed.post/1".to_string(), cid_for("one"));
+        a.insert("app.bsky.feed.post/2".to_string(), cid_for("two"));
+
+        let mut b = Mst::new();
+        b.insert("app.bsky.feed.post/2".to_string(), cid_for("two"));
+        b.insert("app.bsky.feed.post/1".to_string(), cid_for("one"));
+
+        assert_eq!(a.root(), b.root());
+    }
+
+    #[test]
+    fn test_root_changes_when_a_value_changes() {
+        let mut mst = Mst::new();
+        mst.insert("app.bsky.feed.post/1".to_string(), cid_for("one"));
+        let root1 = mst.root();
+
+        mst.insert("app.bsky.feed.post/1".to_string(), cid_for("one-edited"));
+        let root2 = mst.root();
+
+        assert_ne!(root1, root2);
+    }
+
+    #[test]
+    fn test_delete_removes_key() {
+        let mut mst = Mst::new();
+        mst.insert("app.bsky.feed.post/1".to_string(), cid_for("one"));
+        mst.delete("app.bsky.feed.post/1");
+        assert!(mst.get("app.bsky.feed.post/1").is_none());
+        assert!(mst.root().is_none());
+    }
+
+    #[test]
+    fn test_many_keys_builds_multi_layer_tree() {
+        let mut mst = Mst::new();
+        for i in 0..200 {
+            mst.insert(format!("app.bsky.feed.post/{}", i), cid_for(&format!("v{}", i)));
+        }
+        assert!(mst.root().is_some());
+        assert_eq!(mst.len(), 200);
+    }
+
+    #[test]
+    fn test_node_dag_cbor_round_trip() {
+        let mut node = MstNode::default();
+        node.entries.push(MstEntry {
+            key: "app.bsky.feed.post/1".to_string(),
+            value_cid: cid_for("one"),
+            right: None,
+        });
+        node.entries.push(MstEntry {
+            key: "app.bsky.feed.post/2".to_string(),
+            value_cid: cid_for("two"),
+            right: Some(cid_for("subtree")),
+        });
+
+        let bytes = node.to_dag_cbor();
+        let decoded = MstNode::from_dag_cbor(&bytes).unwrap();
+
+        assert_eq!(decoded.entries.len(), node.entries.len());
+        assert_eq!(decoded.entries[0].key, "app.bsky.feed.post/1");
+        assert_eq!(decoded.entries[1].key, "app.bsky.feed.post/2");
+        assert_eq!(decoded.entries[1].right, node.entries[1].right);
+    }
+
+    #[test]
+    fn test_collect_mst_keys_walks_full_tree() {
+        let mut mst = Mst::new();
+        for i in 0..50 {
+            mst.insert(format!("app.bsky.feed.post/{}", i), cid_for(&format!("v{}", i)));
+        }
+        let (root, node_blocks) = mst.root_with_blocks().unwrap();
+        let blocks: HashMap<Cid, Vec<u8>> = node_blocks.into_iter().collect();
+
+        let mut keys = collect_mst_keys(&root, &blocks).unwrap();
+        keys.sort();
+        let mut expected: Vec<String> = (0..50).map(|i| format!("app.bsky.feed.post/{}", i)).collect();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_generate_mst_matches_manual_insert() {
+        let collection = crate::types::Nsid::new("app.bsky.feed.post").unwrap();
+        let records = vec![
+            Record::new(
+                collection.clone(),
+                crate::types::RecordKey::new("1"),
+                serde_json::json!({"text": "a"}),
+            ),
+            Record::new(
+                collection,
+                crate::types::RecordKey::new("2"),
+                serde_json::json!({"text": "b"}),
+            ),
+        ];
+
+        let mut mst = Mst::new();
+        for record in &records {
+            mst.insert(record.path(), record.cid().unwrap());
+        }
+
+        assert_eq!(generate_mst(&records).unwrap(), mst.root());
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_changed() {
+        let mut a = Mst::new();
+        a.insert("a".to_string(), cid_for("a1"));
+        a.insert("b".to_string(), cid_for("b1"));
+
+        let mut b = Mst::new();
+        b.insert("a".to_string(), cid_for("a1"));
+        b.insert("b".to_string(), cid_for("b2"));
+        b.insert("c".to_string(), cid_for("c1"));
+
+        let diff = a.diff(&b);
+        assert_eq!(diff.added, vec!["c".to_string()]);
+        assert_eq!(diff.changed, vec!["b".to_string()]);
+        assert!(diff.removed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_roots_from_blocks_matches_in_memory_diff() {
+        let mut a = Mst::new();
+        a.insert("a".to_string(), cid_for("a1"));
+        a.insert("b".to_string(), cid_for("b1"));
+        let (a_root, a_blocks) = a.root_with_blocks().unwrap();
+
+        let mut b = Mst::new();
+        b.insert("a".to_string(), cid_for("a1"));
+        b.insert("b".to_string(), cid_for("b2"));
+        b.insert("c".to_string(), cid_for("c1"));
+        let (b_root, b_blocks) = b.root_with_blocks().unwrap();
+
+        let mut blocks: HashMap<Cid, Vec<u8>> = a_blocks.into_iter().collect();
+        blocks.extend(b_blocks);
+
+        let diff = diff_roots(Some(&a_root), Some(&b_root), &blocks).unwrap();
+        assert_eq!(diff, a.diff(&b));
+    }
+
+    #[test]
+    fn test_diff_roots_treats_missing_root_as_empty_tree() {
+        let mut mst = Mst::new();
+        mst.insert("a".to_string(), cid_for("a1"));
+        let (root, node_blocks) = mst.root_with_blocks().unwrap();
+        let blocks: HashMap<Cid, Vec<u8>> = node_blocks.into_iter().collect();
+
+        let diff = diff_roots(None, Some(&root), &blocks).unwrap();
+        assert_eq!(diff.added, vec!["a".to_string()]);
+        assert!(diff.changed.is_empty());
+        assert!(diff.removed.is_empty());
+    }
+}
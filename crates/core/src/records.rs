@@ -5,17 +5,17 @@ use crate::types::*;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RecordOp {
     Create {
-        collection: Collection,
+        collection: Nsid,
         rkey: RecordKey,
         value: serde_json::Value,
     },
     Update {
-        collection: Collection,
+        collection: Nsid,
         rkey: RecordKey,
         value: serde_json::Value,
     },
     Delete {
-        collection: Collection,
+        collection: Nsid,
         rkey: RecordKey,
     },
 }
@@ -25,16 +25,35 @@ pub mod keys {
     pub const IDENTITY_KEY: &str = "identity";
     pub const COMMITS_PREFIX: &str = "commits/";
     pub const RECORDS_PREFIX: &str = "records/";
-    
+    pub const MERGE_LOGS_PREFIX: &str = "merge_logs/";
+    pub const RECORD_INDEX_KEY: &str = "record_index";
+    pub const CHECKPOINTS_PREFIX: &str = "checkpoint/";
+
+    pub fn checkpoint_key(version: u64) -> String {
+        format!("{}{}", CHECKPOINTS_PREFIX, version)
+    }
+
+    pub const OUTBOX_PREFIX: &str = "outbox/";
+
+    pub fn outbox_key(idx: u64) -> String {
+        format!("{}{}", OUTBOX_PREFIX, idx)
+    }
+
     pub fn commit_key(version: u64) -> String {
         format!("{}{}", COMMITS_PREFIX, version)
     }
-    
+
     pub fn record_key(collection: &str, rkey: &str) -> String {
         format!("{}{}/{}", RECORDS_PREFIX, collection, rkey)
     }
-    
+
     pub fn collection_prefix(collection: &str) -> String {
         format!("{}{}/", RECORDS_PREFIX, collection)
     }
+
+    /// Key for a record's opt-in CRDT merge log, keyed the same way as the
+    /// record itself so the two can be looked up together.
+    pub fn merge_log_key(collection: &str, rkey: &str) -> String {
+        format!("{}{}/{}", MERGE_LOGS_PREFIX, collection, rkey)
+    }
 }
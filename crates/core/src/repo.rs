@@ -1,544 +1,466 @@
 use crate::{
+    crdt::{LamportStamp, MergeLog},
     error::Result,
     records::{keys, RecordOp},
     traits::{Clock, Crypto, KvStore},
     types::*,
 };
-use sha2::{Digest, Sha256};
-use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Tracks commit ordering without walking the `prev` pointer chain: the
+/// highest commit `idx` (our monotonic commit `version`) seen so far, plus
+/// the highest idx seen per collection, so a caller can ask "what's new in
+/// this collection" without scanning every commit. `prev`/`cid` on each
+/// commit remain for integrity verification, not traversal.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordIndex {
+    pub highest_idx: u64,
+    pub per_collection_tails: HashMap<String, u64>,
+}
+
+/// Cadence, in commits, at which [`Repository::checkpointed_snapshot`]
+/// materializes a checkpoint (Bayou's "keep state every N operations"), so
+/// verifying or restoring such a snapshot only walks the commits after it.
+/// Checkpoints are built when a snapshot is taken, not persisted on each
+/// commit, and old commits are never compacted away.
+const KEEP_STATE_EVERY: u64 = 64;
+
+/// A periodic snapshot of repository state: the full record set and MST
+/// root as of `version`, plus the commit CID at that version. Lets
+/// `Snapshot::verify_chain` start from the latest checkpoint instead of the
+/// first commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u64,
+    pub commit_cid: String,
+    pub mst_root: Option<String>,
+    pub records: Vec<Record>,
+}
+
+/// Whether an outbox entry has been delivered to the remote PDS yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxStatus {
+    Pending,
+    Delivered,
+}
 
-/// The main repository manager
-pub struct Repo<K, C, T>
+/// One queued operation awaiting publish to a remote PDS, keyed by the
+/// same monotonic idx as its commit so replay resumes exactly where it
+/// left off after a crash or reload instead of re-sending everything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub idx: u64,
+    pub op: RecordOp,
+    pub commit_cid: String,
+    pub status: OutboxStatus,
+}
+
+/// Pluggable transport for publishing the outbox to, and pulling updates
+/// from, a remote PDS — so sync logic isn't hard-wired to one host or HTTP
+/// client.
+#[async_trait::async_trait(?Send)]
+pub trait PdsClient {
+    /// Push a batch of outbox entries, in idx order.
+    async fn push(&self, entries: &[OutboxEntry]) -> Result<()>;
+
+    /// Pull every commit the remote has recorded since `since_idx`.
+    async fn pull(&self, since_idx: u64) -> Result<Vec<Commit>>;
+}
+
+/// A synchronous repository manager built directly on the sync
+/// `KvStore`/`Clock`/`Crypto` traits and the real ATProto types, used by
+/// the WASM bindings where every call is a direct (non-`async`) browser
+/// API call. Records and commits are cached in memory on top of `store`
+/// so reads don't round-trip through serialization on every call; call
+/// [`load`](Self::load) once after construction to hydrate that cache
+/// from whatever `store` already has on disk.
+pub struct Repository<S, Cl, Cr>
 where
-    K: KvStore,
-    C: Crypto,
-    T: Clock,
+    S: KvStore,
+    Cl: Clock,
+    Cr: Crypto,
 {
-    store: Arc<K>,
-    crypto: Arc<C>,
-    clock: Arc<T>,
+    did: Did,
+    store: S,
+    clock: Cl,
+    crypto: Cr,
+    records: HashMap<String, Record>,
+    commits: Vec<Commit>,
 }
 
-impl<K, C, T> Repo<K, C, T>
+impl<S, Cl, Cr> Repository<S, Cl, Cr>
 where
-    K: KvStore,
-    C: Crypto,
-    T: Clock,
+    S: KvStore,
+    Cl: Clock,
+    Cr: Crypto,
 {
-    pub fn new(store: Arc<K>, crypto: Arc<C>, clock: Arc<T>) -> Self {
+    /// Create a repository handle over an empty cache. Call
+    /// [`load`](Self::load) afterwards to hydrate it from existing data.
+    pub fn new(did: Did, store: S, clock: Cl, crypto: Cr) -> Self {
         Self {
+            did,
             store,
-            crypto,
             clock,
+            crypto,
+            records: HashMap::new(),
+            commits: Vec::new(),
         }
     }
 
-    /// Initialize a new identity (DID + keypair)
-    pub async fn init_identity(&self) -> Result<Did> {
-        // Check if identity already exists
-        if let Some(existing) = self.get_identity().await? {
-            return Ok(existing);
-        }
-
-        // Generate new keypair and get DID
-        let did = self.crypto.generate_keypair().await?;
-
-        // Store identity
-        self.store
-            .set(keys::IDENTITY_KEY, did.as_bytes().to_vec())
-            .await?;
+    /// The repository owner's DID.
+    pub fn did(&self) -> &Did {
+        &self.did
+    }
 
-        Ok(did)
+    /// The repository's signer, so a caller can fetch its public key (e.g.
+    /// to verify a restored commit chain).
+    pub fn crypto(&self) -> &Cr {
+        &self.crypto
     }
 
-    /// Get the current identity DID
-    pub async fn get_identity(&self) -> Result<Option<Did>> {
-        match self.store.get(keys::IDENTITY_KEY).await? {
-            Some(data) => Ok(Some(String::from_utf8_lossy(&data).to_string())),
-            None => Ok(None),
+    /// Hydrate the in-memory record/commit caches from `store` (e.g.
+    /// after a page reload).
+    pub fn load(&mut self) -> Result<()> {
+        self.records.clear();
+        for key in self.store.list_keys(keys::RECORDS_PREFIX)? {
+            if let Some(data) = self.store.get(&key)? {
+                if let Ok(record) = serde_json::from_slice::<Record>(&data) {
+                    self.records.insert(record.path(), record);
+                }
+            }
+        }
+
+        let mut commits: Vec<(u64, Commit)> = Vec::new();
+        for key in self.store.list_keys(keys::COMMITS_PREFIX)? {
+            if let Some(version_str) = key.strip_prefix(keys::COMMITS_PREFIX) {
+                if let Ok(version) = version_str.parse::<u64>() {
+                    if let Some(data) = self.store.get(&key)? {
+                        if let Ok(commit) = serde_json::from_slice::<Commit>(&data) {
+                            commits.push((version, commit));
+                        }
+                    }
+                }
+            }
         }
+        commits.sort_by_key(|(version, _)| *version);
+        self.commits = commits.into_iter().map(|(_, commit)| commit).collect();
+
+        Ok(())
     }
 
-    /// Create a new record
-    pub async fn create_record(
-        &self,
-        collection: Collection,
+    /// Create a new record, append a signed commit chained to the
+    /// previous one, and return the record's CID.
+    pub fn create_record(
+        &mut self,
+        collection: Nsid,
         rkey: RecordKey,
         value: serde_json::Value,
-    ) -> Result<Record> {
-        let did = self
-            .get_identity()
-            .await?
-            .ok_or_else(|| crate::Error::InvalidOperation("No identity initialized".to_string()))?;
-
-        let timestamp = self.clock.now();
-        let uri = AtUri::new(did.clone(), collection.clone(), rkey.clone());
-
-        // Create record
-        let record = Record {
-            uri: uri.clone(),
-            cid: self.compute_cid(&value)?,
-            value,
-            timestamp,
-        };
-
-        // Store record
-        let key = keys::record_key(&collection, &rkey);
-        let data = serde_json::to_vec(&record)?;
-        self.store.set(&key, data).await?;
-
-        // Create commit
-        let op = RecordOp::Create {
-            collection,
-            rkey,
-            value: record.value.clone(),
-        };
-        self.create_commit(op).await?;
-
-        Ok(record)
+    ) -> Result<Cid> {
+        self.write_record(collection, rkey, value, CommitOp::Create)
     }
 
-    /// Update an existing record (for mutable records like profile)
-    pub async fn update_record(
-        &self,
-        collection: Collection,
+    /// Overwrite an existing record, append a signed commit chained to
+    /// the previous one, and return the record's new CID.
+    pub fn update_record(
+        &mut self,
+        collection: Nsid,
         rkey: RecordKey,
         value: serde_json::Value,
-    ) -> Result<Record> {
-        let did = self
-            .get_identity()
-            .await?
-            .ok_or_else(|| crate::Error::InvalidOperation("No identity initialized".to_string()))?;
-
-        let timestamp = self.clock.now();
-        let uri = AtUri::new(did.clone(), collection.clone(), rkey.clone());
-
-        // Create updated record
-        let record = Record {
-            uri: uri.clone(),
-            cid: self.compute_cid(&value)?,
-            value,
-            timestamp,
-        };
-
-        // Store record
-        let key = keys::record_key(&collection, &rkey);
-        let data = serde_json::to_vec(&record)?;
-        self.store.set(&key, data).await?;
+    ) -> Result<Cid> {
+        self.write_record(collection, rkey, value, CommitOp::Update)
+    }
 
-        // Create commit
-        let op = RecordOp::Update {
-            collection,
-            rkey,
-            value: record.value.clone(),
+    /// Update a record through its CRDT merge log instead of overwriting it
+    /// wholesale, so concurrent edits from two actors sharing the same
+    /// commit history converge instead of whichever write lands last
+    /// silently discarding the other's fields. Diffs `value` against the
+    /// record's current materialized state, appends the resulting ops
+    /// tagged with `(version, actor_id)`, then commits the materialized
+    /// result exactly like [`update_record`](Self::update_record).
+    pub fn update_record_merged(
+        &mut self,
+        collection: Nsid,
+        rkey: RecordKey,
+        actor_id: &str,
+        value: serde_json::Value,
+    ) -> Result<Cid> {
+        let old_value = self
+            .get_record(&collection, &rkey)
+            .map(|record| record.value)
+            .unwrap_or_else(|| serde_json::json!({}));
+
+        let mut log = self.load_merge_log(&collection, &rkey)?;
+        let stamp = LamportStamp {
+            version: log.len() as u64 + 1,
+            actor_id: actor_id.to_string(),
         };
-        self.create_commit(op).await?;
-
-        Ok(record)
-    }
+        log.append_diff(&stamp, &old_value, &value);
+        let merged = log.materialize();
+        self.save_merge_log(&collection, &rkey, &log)?;
 
-    /// Get a record by collection and rkey
-    pub async fn get_record(&self, collection: &str, rkey: &str) -> Result<Option<Record>> {
-        let key = keys::record_key(collection, rkey);
-        match self.store.get(&key).await? {
-            Some(data) => {
-                let record: Record = serde_json::from_slice(&data)?;
-                Ok(Some(record))
-            }
-            None => Ok(None),
-        }
+        self.write_record(collection, rkey, merged, CommitOp::Update)
     }
 
-    /// List all records in a collection
-    pub async fn list_records(&self, collection: &str) -> Result<Vec<Record>> {
-        let prefix = keys::collection_prefix(collection);
-        let keys = self.store.list_keys(&prefix).await?;
-
-        let mut records = Vec::new();
-        for key in keys {
-            if let Some(data) = self.store.get(&key).await? {
-                if let Ok(record) = serde_json::from_slice::<Record>(&data) {
-                    records.push(record);
-                }
-            }
+    fn load_merge_log(&self, collection: &Nsid, rkey: &RecordKey) -> Result<MergeLog> {
+        let key = keys::merge_log_key(collection.as_str(), rkey.as_str());
+        match self.store.get(&key)? {
+            Some(data) => Ok(serde_json::from_slice(&data)?),
+            None => Ok(MergeLog::new()),
         }
-
-        // Sort by timestamp (newest first)
-        records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        Ok(records)
     }
 
-    /// Delete a record
-    pub async fn delete_record(&self, collection: Collection, rkey: RecordKey) -> Result<()> {
-        let key = keys::record_key(&collection, &rkey);
-        self.store.delete(&key).await?;
-
-        // Create commit
-        let op = RecordOp::Delete { collection, rkey };
-        self.create_commit(op).await?;
-
-        Ok(())
+    fn save_merge_log(
+        &mut self,
+        collection: &Nsid,
+        rkey: &RecordKey,
+        log: &MergeLog,
+    ) -> Result<()> {
+        let key = keys::merge_log_key(collection.as_str(), rkey.as_str());
+        self.store.put(&key, &serde_json::to_vec(log)?)
     }
 
-    /// Create a commit for an operation
-    async fn create_commit(&self, op: RecordOp) -> Result<Commit> {
-        let did = self
-            .get_identity()
-            .await?
-            .ok_or_else(|| crate::Error::InvalidOperation("No identity initialized".to_string()))?;
-
-        // Get the latest commit version
-        let version = self.get_latest_version().await? + 1;
-
-        // Get previous commit CID if exists
-        let prev = if version > 1 {
-            self.get_commit(version - 1).await?.map(|c| c.cid)
-        } else {
-            None
+    fn write_record(
+        &mut self,
+        collection: Nsid,
+        rkey: RecordKey,
+        value: serde_json::Value,
+        operation: CommitOp,
+    ) -> Result<Cid> {
+        let record = Record::new(collection.clone(), rkey.clone(), value);
+        record.validate()?;
+        let cid = record.cid()?;
+
+        let op = match operation {
+            CommitOp::Create => RecordOp::Create {
+                collection: collection.clone(),
+                rkey: rkey.clone(),
+                value: record.value.clone(),
+            },
+            CommitOp::Update => RecordOp::Update {
+                collection: collection.clone(),
+                rkey: rkey.clone(),
+                value: record.value.clone(),
+            },
+            CommitOp::Delete => RecordOp::Delete {
+                collection: collection.clone(),
+                rkey: rkey.clone(),
+            },
         };
 
-        // Serialize operation
-        let data = serde_json::to_vec(&op)?;
+        let key = keys::record_key(collection.as_str(), rkey.as_str());
+        self.store.put(&key, &serde_json::to_vec(&record)?)?;
+        self.records.insert(record.path(), record);
 
-        // Sign the commit data
-        let sig = self.crypto.sign(&data).await?;
-
-        let timestamp = self.clock.now();
-
-        let mut commit = Commit {
-            did,
-            version,
-            prev,
-            data: data.clone(),
-            sig,
-            timestamp,
-            cid: String::new(), // Will be computed below
+        let prev = match self.commits.last() {
+            Some(commit) => Some(commit.cid()?),
+            None => None,
         };
 
-        // Compute CID from commit (excluding the CID field itself)
-        let commit_for_cid = serde_json::json!({
-            "did": commit.did,
-            "version": commit.version,
-            "prev": commit.prev,
-            "data": commit.data,
-            "sig": commit.sig,
-            "timestamp": commit.timestamp,
-        });
-        commit.cid = self.compute_cid(&commit_for_cid)?;
-
-        // Store commit
-        let key = keys::commit_key(version);
-        let commit_data = serde_json::to_vec(&commit)?;
-        self.store.set(&key, commit_data).await?;
-
-        Ok(commit)
-    }
-
-    /// Get latest commit version
-    async fn get_latest_version(&self) -> Result<u64> {
-        let keys = self.store.list_keys(keys::COMMITS_PREFIX).await?;
-        let mut max_version = 0u64;
-
-        for key in keys {
-            if let Some(version_str) = key.strip_prefix(keys::COMMITS_PREFIX) {
-                if let Ok(version) = version_str.parse::<u64>() {
-                    max_version = max_version.max(version);
-                }
+        let collection_str = collection.as_str().to_string();
+        let mut commit = Commit::new(
+            self.did.clone(),
+            operation,
+            collection,
+            rkey,
+            Some(cid.clone()),
+            prev,
+        );
+        commit.timestamp = self.clock.now();
+        commit.mst_root = self.mst_root()?;
+        commit.signature = Some(self.crypto.sign(&commit.signing_bytes()?)?);
+        let commit_cid = commit.cid()?;
+        let commit_bytes = serde_json::to_vec(&commit)?;
+
+        // Claim the next commit slot with a compare-and-swap retry loop
+        // instead of a plain put() keyed off self.commits.len(): two
+        // writers racing on the same store (e.g. two tabs sharing one
+        // IndexedDB) can both read the same in-memory length and would
+        // otherwise silently clobber each other's commit at the same idx.
+        // compare_and_swap(key, None, ..) only succeeds for whichever
+        // writer claims the slot first; the loser reloads the index and
+        // retries at the new tail.
+        loop {
+            let mut index = self.load_record_index()?;
+            let idx = index.highest_idx + 1;
+            let commit_key = keys::commit_key(idx);
+            if self
+                .store
+                .compare_and_swap(&commit_key, None, commit_bytes.clone())?
+            {
+                index.highest_idx = idx;
+                index
+                    .per_collection_tails
+                    .insert(collection_str.clone(), idx);
+                self.save_record_index(&index)?;
+                self.enqueue_outbox(idx, op, &commit_cid)?;
+                break;
             }
         }
+        self.commits.push(commit);
 
-        Ok(max_version)
+        Ok(cid)
     }
 
-    /// Get a specific commit
-    async fn get_commit(&self, version: u64) -> Result<Option<Commit>> {
-        let key = keys::commit_key(version);
-        match self.store.get(&key).await? {
-            Some(data) => {
-                let mut commit: Commit = serde_json::from_slice(&data)?;
-                // Recompute CID
-                let commit_for_cid = serde_json::json!({
-                    "did": commit.did,
-                    "version": commit.version,
-                    "prev": commit.prev,
-                    "data": commit.data,
-                    "sig": commit.sig,
-                    "timestamp": commit.timestamp,
-                });
-                commit.cid = self.compute_cid(&commit_for_cid)?;
-                Ok(Some(commit))
-            }
-            None => Ok(None),
+    /// Load the persisted record index, defaulting to an empty one for a
+    /// repository that hasn't written a commit yet.
+    fn load_record_index(&self) -> Result<RecordIndex> {
+        match self.store.get(keys::RECORD_INDEX_KEY)? {
+            Some(data) => Ok(serde_json::from_slice(&data)?),
+            None => Ok(RecordIndex::default()),
         }
     }
 
-    /// Compute CID (simplified version using SHA-256)
-    fn compute_cid(&self, value: &serde_json::Value) -> Result<String> {
-        let json = serde_json::to_vec(value)?;
-        let hash = Sha256::digest(&json);
-        Ok(format!("bafyrei{}", URL_SAFE_NO_PAD.encode(hash)))
+    fn save_record_index(&mut self, index: &RecordIndex) -> Result<()> {
+        self.store
+            .put(keys::RECORD_INDEX_KEY, &serde_json::to_vec(index)?)
     }
 
-    /// Export data for backup
-    pub async fn backup(&self) -> Result<Backup> {
-        let did = self
-            .get_identity()
-            .await?
-            .ok_or_else(|| crate::Error::InvalidOperation("No identity initialized".to_string()))?;
-
-        // Export keypair
-        let keypair = self.crypto.export_keypair().await?;
-
-        // Get all commits
-        let commit_keys = self.store.list_keys(keys::COMMITS_PREFIX).await?;
-        let mut commits = Vec::new();
-        for key in commit_keys {
-            if let Some(data) = self.store.get(&key).await? {
-                if let Ok(commit) = serde_json::from_slice::<Commit>(&data) {
-                    commits.push(commit);
-                }
-            }
-        }
+    /// Queue `op` for delivery to a remote PDS, keyed by the same `idx` as
+    /// its commit so a crash or reload resumes replay exactly where it left
+    /// off instead of re-sending everything already delivered.
+    fn enqueue_outbox(&mut self, idx: u64, op: RecordOp, commit_cid: &Cid) -> Result<()> {
+        let entry = OutboxEntry {
+            idx,
+            op,
+            commit_cid: commit_cid.to_string(),
+            status: OutboxStatus::Pending,
+        };
+        self.store
+            .put(&keys::outbox_key(idx), &serde_json::to_vec(&entry)?)
+    }
 
-        // Get all records
-        let record_keys = self.store.list_keys(keys::RECORDS_PREFIX).await?;
-        let mut records = Vec::new();
-        for key in record_keys {
-            if let Some(data) = self.store.get(&key).await? {
-                if let Ok(record) = serde_json::from_slice::<Record>(&data) {
-                    records.push(record);
+    /// Every outbox entry not yet marked delivered, in idx order.
+    pub fn pending_outbox_entries(&self) -> Result<Vec<OutboxEntry>> {
+        let mut entries = Vec::new();
+        for key in self.store.list_keys(keys::OUTBOX_PREFIX)? {
+            if let Some(data) = self.store.get(&key)? {
+                if let Ok(entry) = serde_json::from_slice::<OutboxEntry>(&data) {
+                    if entry.status == OutboxStatus::Pending {
+                        entries.push(entry);
+                    }
                 }
             }
         }
-
-        Ok(Backup {
-            version: "1.0".to_string(),
-            did,
-            keypair,
-            commits,
-            records,
-            timestamp: self.clock.now(),
-        })
+        entries.sort_by_key(|entry| entry.idx);
+        Ok(entries)
     }
 
-    /// Restore from backup
-    pub async fn restore(&self, backup: Backup) -> Result<()> {
-        // Clear existing data
-        self.store.clear().await?;
-
-        // Import keypair
-        self.crypto.import_keypair(&backup.keypair).await?;
-
-        // Store identity
-        self.store
-            .set(keys::IDENTITY_KEY, backup.did.as_bytes().to_vec())
-            .await?;
-
-        // Restore commits
-        for commit in backup.commits {
-            let key = keys::commit_key(commit.version);
-            let data = serde_json::to_vec(&commit)?;
-            self.store.set(&key, data).await?;
+    /// Push every pending outbox entry to `client` and mark them delivered,
+    /// so offline edits made while `client` was unreachable get replayed in
+    /// idx order the next time sync runs.
+    pub async fn sync_outbox<P: PdsClient>(&mut self, client: &P) -> Result<()> {
+        let pending = self.pending_outbox_entries()?;
+        if pending.is_empty() {
+            return Ok(());
         }
 
-        // Restore records
-        for record in backup.records {
-            let key = keys::record_key(&record.uri.collection, &record.uri.rkey);
-            let data = serde_json::to_vec(&record)?;
-            self.store.set(&key, data).await?;
-        }
+        client.push(&pending).await?;
 
+        for entry in &pending {
+            let mut delivered = entry.clone();
+            delivered.status = OutboxStatus::Delivered;
+            self.store
+                .put(&keys::outbox_key(entry.idx), &serde_json::to_vec(&delivered)?)?;
+        }
         Ok(())
     }
 
-    /// Export records for publishing to external PDS
-    pub async fn export_for_publish(&self) -> Result<Vec<Record>> {
-        // Get all records
-        let record_keys = self.store.list_keys(keys::RECORDS_PREFIX).await?;
-        let mut records = Vec::new();
-        for key in record_keys {
-            if let Some(data) = self.store.get(&key).await? {
-                if let Ok(record) = serde_json::from_slice::<Record>(&data) {
-                    records.push(record);
-                }
-            }
-        }
-        Ok(records)
+    /// Look up a single record by collection and rkey.
+    pub fn get_record(&self, collection: &Nsid, rkey: &RecordKey) -> Option<Record> {
+        let path = format!("{}/{}", collection.as_str(), rkey.as_str());
+        self.records.get(&path).cloned()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
-    use std::sync::Mutex;
-
-    // Mock implementations for testing
-    struct MockKvStore {
-        data: Mutex<HashMap<String, Vec<u8>>>,
+    /// All records in `collection`, newest first.
+    pub fn list_records(&self, collection: &Nsid) -> Vec<Record> {
+        let mut records: Vec<Record> = self
+            .records
+            .values()
+            .filter(|r| r.collection.as_str() == collection.as_str())
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        records
     }
 
-    impl MockKvStore {
-        fn new() -> Self {
-            Self {
-                data: Mutex::new(HashMap::new()),
-            }
-        }
+    /// All commits, in order.
+    pub fn get_commits(&self) -> Result<Vec<Commit>> {
+        Ok(self.commits.clone())
     }
 
-    #[async_trait::async_trait(?Send)]
-    impl KvStore for MockKvStore {
-        async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
-            Ok(self.data.lock().unwrap().get(key).cloned())
-        }
-
-        async fn set(&self, key: &str, value: Vec<u8>) -> Result<()> {
-            self.data.lock().unwrap().insert(key.to_string(), value);
-            Ok(())
-        }
-
-        async fn delete(&self, key: &str) -> Result<()> {
-            self.data.lock().unwrap().remove(key);
-            Ok(())
-        }
-
-        async fn list_keys(&self, prefix: &str) -> Result<Vec<String>> {
-            Ok(self
-                .data
-                .lock()
-                .unwrap()
-                .keys()
-                .filter(|k| k.starts_with(prefix))
-                .cloned()
-                .collect())
-        }
-
-        async fn clear(&self) -> Result<()> {
-            self.data.lock().unwrap().clear();
-            Ok(())
-        }
+    /// Every record across all collections, in no particular order.
+    pub fn all_records(&self) -> Vec<Record> {
+        self.records.values().cloned().collect()
     }
 
-    struct MockCrypto {
-        did: Mutex<Option<String>>,
+    /// Root CID of the Merkle Search Tree over the current record set, or
+    /// `None` if the repository has no records yet.
+    pub fn mst_root(&self) -> Result<Option<Cid>> {
+        crate::mst::generate_mst(&self.all_records())
     }
 
-    impl MockCrypto {
-        fn new() -> Self {
-            Self {
-                did: Mutex::new(None),
-            }
-        }
+    /// Build a checkpoint-plus-tail snapshot (see
+    /// [`Snapshot::with_checkpoint`](crate::snapshot::Snapshot::with_checkpoint))
+    /// using this repository's own checkpoint cadence, so integrity checks
+    /// and restores never need to walk the full commit history once it
+    /// grows past [`KEEP_STATE_EVERY`] commits.
+    pub fn checkpointed_snapshot(&self) -> Result<crate::snapshot::Snapshot> {
+        crate::snapshot::Snapshot::with_checkpoint(self, KEEP_STATE_EVERY as usize)
     }
 
-    #[async_trait::async_trait(?Send)]
-    impl Crypto for MockCrypto {
-        async fn generate_keypair(&self) -> Result<String> {
-            let did = "did:key:z6MkTest123".to_string();
-            *self.did.lock().unwrap() = Some(did.clone());
-            Ok(did)
-        }
-
-        async fn sign(&self, _data: &[u8]) -> Result<Vec<u8>> {
-            Ok(vec![0u8; 64])
-        }
-
-        async fn verify(&self, _data: &[u8], _signature: &[u8], _public_key: &str) -> Result<bool> {
-            Ok(true)
-        }
-
-        async fn get_did(&self) -> Result<Option<String>> {
-            Ok(self.did.lock().unwrap().clone())
-        }
-
-        async fn export_keypair(&self) -> Result<Vec<u8>> {
-            Ok(vec![0u8; 32])
+    /// Load the records and commits from `snapshot` into this repository,
+    /// after verifying its entire commit chain against `public_key` (each
+    /// `prev` pointer against the actual previous commit CID, and each
+    /// commit's signature). Snapshot records are written over any record
+    /// at the same path; records absent from the snapshot are kept. The
+    /// in-memory commit list is replaced by the snapshot's commits. Rejects
+    /// the restore outright, leaving the repository untouched, if any link
+    /// or signature fails to verify.
+    pub fn restore_from_snapshot(
+        &mut self,
+        snapshot: &crate::snapshot::Snapshot,
+        public_key: &[u8],
+    ) -> Result<()> {
+        if !snapshot.verify_chain(public_key)? {
+            return Err(crate::Error::InvalidCommit(
+                "Commit chain verification failed".to_string(),
+            ));
         }
 
-        async fn import_keypair(&self, _data: &[u8]) -> Result<String> {
-            let did = "did:key:z6MkTest123".to_string();
-            *self.did.lock().unwrap() = Some(did.clone());
-            Ok(did)
+        for record in &snapshot.records {
+            let key = keys::record_key(record.collection.as_str(), record.rkey.as_str());
+            self.store.put(&key, &serde_json::to_vec(record)?)?;
+            self.records.insert(record.path(), record.clone());
         }
-    }
-
-    struct MockClock;
-
-    impl Clock for MockClock {
-        fn now(&self) -> u64 {
-            1234567890000
-        }
-    }
-
-    #[tokio::test]
-    async fn test_init_identity() {
-        let store = Arc::new(MockKvStore::new());
-        let crypto = Arc::new(MockCrypto::new());
-        let clock = Arc::new(MockClock);
-        let repo = Repo::new(store, crypto, clock);
-
-        let did = repo.init_identity().await.unwrap();
-        assert_eq!(did, "did:key:z6MkTest123");
-
-        // Should return same DID on second call
-        let did2 = repo.init_identity().await.unwrap();
-        assert_eq!(did, did2);
-    }
-
-    #[tokio::test]
-    async fn test_create_and_get_record() {
-        let store = Arc::new(MockKvStore::new());
-        let crypto = Arc::new(MockCrypto::new());
-        let clock = Arc::new(MockClock);
-        let repo = Repo::new(store, crypto, clock);
-
-        repo.init_identity().await.unwrap();
-
-        let value = serde_json::json!({
-            "$type": "app.bsky.feed.post",
-            "text": "Hello World!",
-            "created_at": "2025-01-01T00:00:00Z"
-        });
-
-        let record = repo
-            .create_record("app.bsky.feed.post".to_string(), "test123".to_string(), value)
-            .await
-            .unwrap();
-
-        assert_eq!(record.uri.collection, "app.bsky.feed.post");
-        assert_eq!(record.uri.rkey, "test123");
-
-        let fetched = repo
-            .get_record("app.bsky.feed.post", "test123")
-            .await
-            .unwrap();
-        assert!(fetched.is_some());
-        assert_eq!(fetched.unwrap().value["text"], "Hello World!");
-    }
 
-    #[tokio::test]
-    async fn test_list_records() {
-        let store = Arc::new(MockKvStore::new());
-        let crypto = Arc::new(MockCrypto::new());
-        let clock = Arc::new(MockClock);
-        let repo = Repo::new(store, crypto, clock);
-
-        repo.init_identity().await.unwrap();
-
-        // Create multiple posts
-        for i in 1..=3 {
-            let value = serde_json::json!({
-                "$type": "app.bsky.feed.post",
-                "text": format!("Post {}", i),
-                "created_at": "2025-01-01T00:00:00Z"
-            });
-            repo.create_record("app.bsky.feed.post".to_string(), format!("post{}", i), value)
-                .await
-                .unwrap();
+        let tail_start = match &snapshot.checkpoint {
+            Some(checkpoint) => {
+                let checkpoint_key = keys::checkpoint_key(checkpoint.version);
+                self.store.put(&checkpoint_key, &serde_json::to_vec(checkpoint)?)?;
+                checkpoint.version
+            }
+            None => 0,
+        };
+        // Keep the record index in step with the commit slots written here,
+        // so the next write_record claims the slot after them.
+        let mut index = RecordIndex {
+            highest_idx: tail_start,
+            ..RecordIndex::default()
+        };
+        for (offset, commit) in snapshot.commits.iter().enumerate() {
+            let idx = tail_start + offset as u64 + 1;
+            let commit_key = keys::commit_key(idx);
+            self.store.put(&commit_key, &serde_json::to_vec(commit)?)?;
+            index.highest_idx = idx;
+            index
+                .per_collection_tails
+                .insert(commit.collection.as_str().to_string(), idx);
         }
+        self.save_record_index(&index)?;
+        self.commits = snapshot.commits.clone();
 
-        let records = repo.list_records("app.bsky.feed.post").await.unwrap();
-        assert_eq!(records.len(), 3);
+        Ok(())
     }
 }
+
@@ -1,10 +1,12 @@
 //! JSON snapshot serializer for repository export
 
-use crate::error::Result;
-use crate::repo::Repository;
+use crate::car::{self, CarBlock};
+use crate::error::{Error, Result};
+use crate::repo::{Checkpoint, Repository};
 use crate::traits::{Clock, Crypto, KvStore};
-use crate::types::{Commit, Record};
+use crate::types::{Cid, Commit, Record};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// A complete repository snapshot for export/import
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,12 +15,20 @@ pub struct Snapshot {
     pub did: String,
     /// All records in the repository
     pub records: Vec<Record>,
-    /// All commits in the repository (in order)
+    /// Commits in the repository: the full ordered log, or — when built
+    /// via [`with_checkpoint`](Self::with_checkpoint) — only the commits
+    /// appended after `checkpoint`'s boundary.
     pub commits: Vec<Commit>,
+    /// The latest checkpoint, if this snapshot was built with one; `commits`
+    /// then holds only the trailing commits since `checkpoint.version`.
+    pub checkpoint: Option<Checkpoint>,
     /// Export timestamp
     pub exported_at: String,
     /// Format version
     pub version: String,
+    /// Root CID of the Merkle Search Tree over `records` at export time, if
+    /// any records exist.
+    pub mst_root: Option<String>,
 }
 
 impl Snapshot {
@@ -29,16 +39,68 @@ impl Snapshot {
         // Get all commits
         let commits = repo.get_commits()?;
 
-        // For simplicity, we'll create an empty records list for now
-        // In a real implementation, we'd iterate through all collections
-        let all_records = Vec::new();
+        let all_records = repo.all_records();
+        let mst_root = crate::mst::generate_mst(&all_records)?.map(|cid| cid.to_string());
 
         Ok(Snapshot {
             did: repo.did().to_string(),
             records: all_records,
             commits,
+            checkpoint: None,
             exported_at: chrono::Utc::now().to_rfc3339(),
             version: "1.0.0".to_string(),
+            mst_root,
+        })
+    }
+
+    /// Create a snapshot using a checkpoint-plus-tail scheme (a CRDT oplog
+    /// pattern): every `every` commits materializes a full record-set
+    /// checkpoint, and `commits` then holds only the commits appended after
+    /// the latest such boundary. Lets a loader verify and fast-forward the
+    /// trailing commits instead of walking the entire commit history.
+    ///
+    /// This repository only tracks the current record set rather than a
+    /// per-commit history, so the checkpoint's record set and MST root are
+    /// the state as of this call, not as of `checkpoint.version`: records
+    /// written by the trailing commits are already in the checkpoint.
+    pub fn with_checkpoint<S: KvStore, Cl: Clock, Cr: Crypto>(
+        repo: &Repository<S, Cl, Cr>,
+        every: usize,
+    ) -> Result<Self> {
+        let commits = repo.get_commits()?;
+        let all_records = repo.all_records();
+        let mst_root = crate::mst::generate_mst(&all_records)?.map(|cid| cid.to_string());
+
+        if every == 0 || commits.len() < every {
+            return Ok(Snapshot {
+                did: repo.did().to_string(),
+                records: all_records,
+                commits,
+                checkpoint: None,
+                exported_at: chrono::Utc::now().to_rfc3339(),
+                version: "1.0.0".to_string(),
+                mst_root,
+            });
+        }
+
+        let checkpoint_at = (commits.len() / every) * every;
+        let checkpoint_commit = &commits[checkpoint_at - 1];
+        let checkpoint = Checkpoint {
+            version: checkpoint_at as u64,
+            commit_cid: checkpoint_commit.cid()?.to_string(),
+            mst_root: mst_root.clone(),
+            records: all_records.clone(),
+        };
+        let tail_commits = commits[checkpoint_at..].to_vec();
+
+        Ok(Snapshot {
+            did: repo.did().to_string(),
+            records: all_records,
+            commits: tail_commits,
+            checkpoint: Some(checkpoint),
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            version: "1.0.0".to_string(),
+            mst_root,
         })
     }
 
@@ -61,6 +123,112 @@ impl Snapshot {
     pub fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
         Ok(serde_json::from_slice(bytes)?)
     }
+
+    /// Verify this snapshot's commit chain: `self.commits` (assumed
+    /// chronological, and — when a checkpoint is present — only the tail
+    /// since its boundary) must form an unbroken chain where each commit's
+    /// `prev` matches the previous commit's own CID (the checkpoint's
+    /// `commit_cid`, for the first tail commit), and each commit's
+    /// signature must verify against `public_key`.
+    pub fn verify_chain(&self, public_key: &[u8]) -> Result<bool> {
+        let crypto = crate::traits::Ed25519Crypto::new();
+        let mut expected_prev: Option<Cid> = match &self.checkpoint {
+            Some(checkpoint) => Some(Cid::from_string(checkpoint.commit_cid.clone())?),
+            None => None,
+        };
+
+        for commit in &self.commits {
+            if commit.prev != expected_prev {
+                return Ok(false);
+            }
+            let signature = match &commit.signature {
+                Some(sig) => sig,
+                None => return Ok(false),
+            };
+            if !crypto.verify(&commit.signing_bytes()?, signature, public_key)? {
+                return Ok(false);
+            }
+            expected_prev = Some(commit.cid()?);
+        }
+
+        Ok(true)
+    }
+
+    /// Export this snapshot as a CAR v1 byte stream: a DAG-CBOR header
+    /// (with a genuine CBOR CID-link root per the CAR v1 spec, see
+    /// [`car`](crate::car)) naming the latest commit as the sole root,
+    /// followed by one block per commit and one block per record, each
+    /// keyed by the CIDv1 hash of its own serialized bytes. This is enough
+    /// for this crate's own [`from_car`](Self::from_car) to round-trip a
+    /// snapshot, and when the snapshot has at least one commit the root
+    /// resolves to a block within the same file (with no commits the root
+    /// is the CID of the DID's bytes, which names no block in the file) —
+    /// but the block CIDs are a hash of the full JSON-serialized
+    /// `Commit`/`Record`, not the DAG-CBOR CID those types advertise via
+    /// [`Commit::cid`](crate::types::Commit::cid)/
+    /// [`Record::cid`](crate::types::Record::cid). A real ATProto client
+    /// resolving `com.atproto.sync.getRepo` blocks by those CIDs won't find
+    /// them here; see the caveat on [`car`](crate::car).
+    pub fn to_car(&self) -> Result<Vec<u8>> {
+        let mut blocks = Vec::new();
+        let mut root = None;
+        for commit in &self.commits {
+            let data = serde_json::to_vec(commit)?;
+            let cid = Cid::from_bytes(&data);
+            root = Some(cid.clone());
+            blocks.push(CarBlock { cid, data });
+        }
+        for record in &self.records {
+            let data = serde_json::to_vec(record)?;
+            let cid = Cid::from_bytes(&data);
+            blocks.push(CarBlock { cid, data });
+        }
+
+        let root = root.unwrap_or_else(|| Cid::from_bytes(self.did.as_bytes()));
+        car::write_car(&root, &blocks)
+    }
+
+    /// Parse a CAR v1 byte stream written by [`to_car`](Self::to_car),
+    /// rebuilding a snapshot's commits and records from every block.
+    /// [`car::read_car`] already rejects truncated frames and blocks whose
+    /// bytes don't hash to their framed CID; this additionally rejects a
+    /// duplicate CID outright rather than silently dropping data.
+    pub fn from_car(bytes: &[u8]) -> Result<Self> {
+        let (_header, blocks) = car::read_car(bytes)?;
+
+        let mut seen_cids = HashSet::new();
+        let mut commits = Vec::new();
+        let mut records = Vec::new();
+        let mut did = String::new();
+
+        for block in blocks {
+            if !seen_cids.insert(block.cid) {
+                return Err(Error::SerializationError(
+                    "Duplicate CID in CAR file".to_string(),
+                ));
+            }
+
+            if let Ok(commit) = serde_json::from_slice::<Commit>(&block.data) {
+                did = commit.did.as_str().to_string();
+                commits.push(commit);
+            } else if let Ok(record) = serde_json::from_slice::<Record>(&block.data) {
+                records.push(record);
+            }
+        }
+
+        commits.sort_by(|a: &Commit, b: &Commit| a.timestamp.cmp(&b.timestamp));
+        let mst_root = crate::mst::generate_mst(&records)?.map(|cid| cid.to_string());
+
+        Ok(Snapshot {
+            did,
+            records,
+            commits,
+            checkpoint: None,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            version: "1.0.0".to_string(),
+            mst_root,
+        })
+    }
 }
 
 /// Record export format for individual record snapshots
@@ -211,6 +379,8 @@ mod tests {
         let snapshot = Snapshot::from_repo(&repo).unwrap();
         assert_eq!(snapshot.did, "did:plc:test123");
         assert_eq!(snapshot.commits.len(), 2);
+        assert_eq!(snapshot.records.len(), 2);
+        assert!(snapshot.mst_root.is_some());
 
         // Test JSON serialization
         let json = snapshot.to_json().unwrap();
@@ -222,6 +392,121 @@ mod tests {
         assert_eq!(loaded.commits.len(), 2);
     }
 
+    #[test]
+    fn test_snapshot_car_round_trip() {
+        let mut repo = setup_repo();
+
+        let collection = Nsid::new("app.bsky.feed.post").unwrap();
+        for i in 0..2 {
+            let rkey = RecordKey::new(format!("post{}", i));
+            let value = serde_json::json!({
+                "text": format!("Post {}", i),
+            });
+            repo.create_record(collection.clone(), rkey, value).unwrap();
+        }
+
+        let snapshot = Snapshot::from_repo(&repo).unwrap();
+        let car_bytes = snapshot.to_car().unwrap();
+
+        let loaded = Snapshot::from_car(&car_bytes).unwrap();
+        assert_eq!(loaded.did, snapshot.did);
+        assert_eq!(loaded.commits.len(), snapshot.commits.len());
+    }
+
+    #[test]
+    fn test_verify_chain_accepts_valid_commits_and_rejects_wrong_key() {
+        let mut repo = setup_repo();
+
+        let collection = Nsid::new("app.bsky.feed.post").unwrap();
+        for i in 0..3 {
+            let rkey = RecordKey::new(format!("post{}", i));
+            let value = serde_json::json!({ "text": format!("Post {}", i) });
+            repo.create_record(collection.clone(), rkey, value).unwrap();
+        }
+
+        let snapshot = Snapshot::from_repo(&repo).unwrap();
+        use crate::traits::Crypto;
+        let public_key = repo.crypto().public_key();
+        assert!(snapshot.verify_chain(&public_key).unwrap());
+
+        let wrong_key = Ed25519Crypto::new().public_key();
+        assert!(!snapshot.verify_chain(&wrong_key).unwrap());
+    }
+
+    #[test]
+    fn test_verify_chain_rejects_broken_prev_link() {
+        let mut repo = setup_repo();
+        let collection = Nsid::new("app.bsky.feed.post").unwrap();
+        for i in 0..2 {
+            let rkey = RecordKey::new(format!("post{}", i));
+            let value = serde_json::json!({ "text": format!("Post {}", i) });
+            repo.create_record(collection.clone(), rkey, value).unwrap();
+        }
+
+        let mut snapshot = Snapshot::from_repo(&repo).unwrap();
+        snapshot.commits[1].prev = None;
+
+        use crate::traits::Crypto;
+        let public_key = repo.crypto().public_key();
+        assert!(!snapshot.verify_chain(&public_key).unwrap());
+    }
+
+    #[test]
+    fn test_with_checkpoint_keeps_only_trailing_commits() {
+        let mut repo = setup_repo();
+        let collection = Nsid::new("app.bsky.feed.post").unwrap();
+        for i in 0..10 {
+            let rkey = RecordKey::new(format!("post{}", i));
+            let value = serde_json::json!({ "text": format!("Post {}", i) });
+            repo.create_record(collection.clone(), rkey, value).unwrap();
+        }
+
+        let snapshot = Snapshot::with_checkpoint(&repo, 4).unwrap();
+        let checkpoint = snapshot.checkpoint.as_ref().unwrap();
+        assert_eq!(checkpoint.version, 8);
+        assert_eq!(checkpoint.records.len(), 10);
+        assert_eq!(snapshot.commits.len(), 2);
+
+        use crate::traits::Crypto;
+        let public_key = repo.crypto().public_key();
+        assert!(snapshot.verify_chain(&public_key).unwrap());
+    }
+
+    #[test]
+    fn test_with_checkpoint_falls_back_to_full_history_below_threshold() {
+        let mut repo = setup_repo();
+        let collection = Nsid::new("app.bsky.feed.post").unwrap();
+        repo.create_record(collection, RecordKey::new("only"), serde_json::json!({"text": "a"}))
+            .unwrap();
+
+        let snapshot = Snapshot::with_checkpoint(&repo, 64).unwrap();
+        assert!(snapshot.checkpoint.is_none());
+        assert_eq!(snapshot.commits.len(), 1);
+    }
+
+    #[test]
+    fn test_restore_from_checkpoint_snapshot() {
+        let mut repo = setup_repo();
+        let collection = Nsid::new("app.bsky.feed.post").unwrap();
+        for i in 0..6 {
+            let rkey = RecordKey::new(format!("post{}", i));
+            let value = serde_json::json!({ "text": format!("Post {}", i) });
+            repo.create_record(collection.clone(), rkey, value).unwrap();
+        }
+
+        let snapshot = Snapshot::with_checkpoint(&repo, 4).unwrap();
+
+        use crate::traits::Crypto;
+        let public_key = repo.crypto().public_key();
+
+        let mut restored = setup_repo();
+        restored
+            .restore_from_snapshot(&snapshot, &public_key)
+            .unwrap();
+        assert_eq!(restored.all_records().len(), 6);
+        assert_eq!(restored.get_commits().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_snapshot_bytes() {
         let mut repo = setup_repo();
@@ -3,6 +3,8 @@
 use crate::error::Result;
 use chrono::{DateTime, Utc};
 
+pub use crate::did_key::KeyType;
+
 /// Key-Value storage abstraction for persisting repository data
 pub trait KvStore: Send + Sync {
     /// Store a value with the given key
@@ -19,6 +21,53 @@ pub trait KvStore: Send + Sync {
 
     /// List all keys with a given prefix
     fn list_keys(&self, prefix: &str) -> Result<Vec<String>>;
+
+    /// Atomically replace the value at `key` with `new`, but only if the
+    /// value currently stored there equals `expected` (`None` meaning "key
+    /// must not exist yet"). Returns `true` if the swap took effect and
+    /// `false` if another writer got there first, in which case the caller
+    /// should re-read the current state and retry with a fresh `new`.
+    ///
+    /// The default implementation is a plain read-then-write and is only
+    /// safe because `KvStore` methods take `&mut self` for writes — a
+    /// backing store with shared/concurrent access (a real database, a
+    /// tab-shared IndexedDB connection) must override this with a genuine
+    /// atomic compare-and-swap.
+    fn compare_and_swap(
+        &mut self,
+        key: &str,
+        expected: Option<Vec<u8>>,
+        new: Vec<u8>,
+    ) -> Result<bool> {
+        if self.get(key)? == expected {
+            self.put(key, &new)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+/// Async counterpart to [`KvStore`], for backends where storage I/O is
+/// genuinely asynchronous rather than synchronous work wrapped in `async fn`
+/// — e.g. a browser's IndexedDB, reached through `web-sys` promises. A
+/// backend can implement both traits at once: `KvStore` serving reads/writes
+/// out of an in-memory cache so `Repository` keeps its synchronous surface,
+/// and `AsyncKvStore` performing the real round trip against the underlying
+/// store.
+#[async_trait::async_trait(?Send)]
+pub trait AsyncKvStore {
+    /// Store a value with the given key
+    async fn put(&self, key: &str, value: &[u8]) -> Result<()>;
+
+    /// Retrieve a value by key
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Delete a value by key
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// List all keys with a given prefix
+    async fn list_keys(&self, prefix: &str) -> Result<Vec<String>>;
 }
 
 /// Clock abstraction for deterministic timestamp generation
@@ -37,6 +86,12 @@ pub trait Crypto: Send + Sync {
 
     /// Get the public key bytes
     fn public_key(&self) -> Vec<u8>;
+
+    /// Which curve this signer uses. Defaults to `Ed25519` so existing
+    /// implementors don't need to change; [`Secp256k1Crypto`] overrides it.
+    fn key_type(&self) -> KeyType {
+        KeyType::Ed25519
+    }
 }
 
 /// Default system clock implementation
@@ -90,6 +145,19 @@ impl KvStore for MemoryKvStore {
             .collect();
         Ok(keys)
     }
+
+    fn compare_and_swap(
+        &mut self,
+        key: &str,
+        expected: Option<Vec<u8>>,
+        new: Vec<u8>,
+    ) -> Result<bool> {
+        if self.data.get(key).cloned() != expected {
+            return Ok(false);
+        }
+        self.data.insert(key.to_string(), new);
+        Ok(true)
+    }
 }
 
 /// Mock crypto implementation using ed25519
@@ -115,6 +183,22 @@ impl Ed25519Crypto {
             keypair: signing_key,
         }
     }
+
+    /// The raw 32-byte seed, for callers that need to persist it (e.g.
+    /// `WebCrypto`'s localStorage-backed keypair slot).
+    pub fn private_key_bytes(&self) -> Vec<u8> {
+        self.keypair.to_bytes().to_vec()
+    }
+
+    /// Generate a new keypair for `key_type` instead of always Ed25519.
+    /// Ed25519 and secp256k1 keys have different representations, so this
+    /// returns a boxed signer rather than `Self`.
+    pub fn generate_keypair_with(key_type: KeyType) -> Box<dyn Crypto> {
+        match key_type {
+            KeyType::Ed25519 => Box::new(Ed25519Crypto::new()),
+            KeyType::Secp256k1 => Box::new(Secp256k1Crypto::new()),
+        }
+    }
 }
 
 impl Default for Ed25519Crypto {
@@ -131,26 +215,113 @@ impl Crypto for Ed25519Crypto {
     }
 
     fn verify(&self, data: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
-        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        ed25519_verify(data, signature, public_key)
+    }
 
-        let verifying_key = VerifyingKey::from_bytes(
-            public_key
-                .try_into()
-                .map_err(|_| crate::error::Error::CryptoError("Invalid public key".to_string()))?,
-        )
-        .map_err(|e| crate::error::Error::CryptoError(e.to_string()))?;
+    fn public_key(&self) -> Vec<u8> {
+        self.keypair.verifying_key().to_bytes().to_vec()
+    }
+}
 
-        let signature = Signature::from_bytes(
-            signature
-                .try_into()
-                .map_err(|_| crate::error::Error::CryptoError("Invalid signature".to_string()))?,
-        );
+/// secp256k1 crypto implementation, ATProto's other supported signing
+/// curve. Signatures are ECDSA over the SHA-256 digest of the message,
+/// normalized to low-S form as ATProto requires, produced with the `k256`
+/// crate.
+#[derive(Debug, Clone)]
+pub struct Secp256k1Crypto {
+    signing_key: k256::ecdsa::SigningKey,
+}
 
-        Ok(verifying_key.verify(data, &signature).is_ok())
+impl Secp256k1Crypto {
+    /// Create a new crypto instance with a random keypair
+    pub fn new() -> Self {
+        let signing_key = k256::ecdsa::SigningKey::random(&mut rand::rngs::OsRng);
+        Secp256k1Crypto { signing_key }
+    }
+
+    /// Create from an existing 32-byte scalar
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self> {
+        let signing_key = k256::ecdsa::SigningKey::from_bytes(bytes.into())
+            .map_err(|e| crate::error::Error::CryptoError(e.to_string()))?;
+        Ok(Secp256k1Crypto { signing_key })
+    }
+
+    /// The raw 32-byte private scalar, for callers that need to persist it
+    /// (e.g. `WebCrypto`'s localStorage-backed keypair slot).
+    pub fn private_key_bytes(&self) -> Vec<u8> {
+        self.signing_key.to_bytes().to_vec()
+    }
+}
+
+impl Default for Secp256k1Crypto {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crypto for Secp256k1Crypto {
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        use k256::ecdsa::signature::Signer;
+        let signature: k256::ecdsa::Signature = self.signing_key.sign(data);
+        Ok(signature.normalize_s().unwrap_or(signature).to_vec())
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
+        secp256k1_verify(data, signature, public_key)
     }
 
     fn public_key(&self) -> Vec<u8> {
-        self.keypair.verifying_key().to_bytes().to_vec()
+        self.signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn key_type(&self) -> KeyType {
+        KeyType::Secp256k1
+    }
+}
+
+fn secp256k1_verify(data: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
+    use k256::ecdsa::signature::Verifier;
+
+    let verifying_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|e| crate::error::Error::CryptoError(e.to_string()))?;
+    let signature = k256::ecdsa::Signature::from_slice(signature)
+        .map_err(|e| crate::error::Error::CryptoError(e.to_string()))?;
+
+    Ok(verifying_key.verify(data, &signature).is_ok())
+}
+
+fn ed25519_verify(data: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    let verifying_key = VerifyingKey::from_bytes(
+        public_key
+            .try_into()
+            .map_err(|_| crate::error::Error::CryptoError("Invalid public key".to_string()))?,
+    )
+    .map_err(|e| crate::error::Error::CryptoError(e.to_string()))?;
+
+    let signature = Signature::from_bytes(
+        signature
+            .try_into()
+            .map_err(|_| crate::error::Error::CryptoError("Invalid signature".to_string()))?,
+    );
+
+    Ok(verifying_key.verify(data, &signature).is_ok())
+}
+
+/// Verify `signature` over `data` against a `did:key` identifier, reading
+/// the signing curve from its multicodec prefix and dispatching to the
+/// matching verifier — so a commit authored by a peer using either curve
+/// can be checked without knowing in advance which one they used.
+pub fn verify_by_did(data: &[u8], signature: &[u8], did: &str) -> Result<bool> {
+    let (key_type, public_key) = crate::did_key::did_to_bytes(did)?;
+    match key_type {
+        KeyType::Ed25519 => ed25519_verify(data, signature, &public_key),
+        KeyType::Secp256k1 => secp256k1_verify(data, signature, &public_key),
     }
 }
 
@@ -184,6 +355,24 @@ mod tests {
         assert_eq!(keys.len(), 2);
     }
 
+    #[test]
+    fn test_compare_and_swap() {
+        let mut store = MemoryKvStore::new();
+
+        // Claiming a fresh key requires `expected: None`.
+        assert!(store.compare_and_swap("slot", None, b"first".to_vec()).unwrap());
+
+        // A second writer racing against the same expected value loses.
+        assert!(!store.compare_and_swap("slot", None, b"second".to_vec()).unwrap());
+        assert_eq!(store.get("slot").unwrap(), Some(b"first".to_vec()));
+
+        // Swapping with the correct expected value succeeds.
+        assert!(store
+            .compare_and_swap("slot", Some(b"first".to_vec()), b"second".to_vec())
+            .unwrap());
+        assert_eq!(store.get("slot").unwrap(), Some(b"second".to_vec()));
+    }
+
     #[test]
     fn test_system_clock() {
         let clock = SystemClock;
@@ -206,4 +395,37 @@ mod tests {
         let wrong_data = b"wrong message";
         assert!(!crypto.verify(wrong_data, &signature, &public_key).unwrap());
     }
+
+    #[test]
+    fn test_secp256k1_crypto() {
+        let crypto = Secp256k1Crypto::new();
+        assert_eq!(crypto.key_type(), KeyType::Secp256k1);
+
+        let data = b"test message";
+        let signature = crypto.sign(data).unwrap();
+        let public_key = crypto.public_key();
+
+        assert!(crypto.verify(data, &signature, &public_key).unwrap());
+        assert!(!crypto.verify(b"wrong message", &signature, &public_key).unwrap());
+    }
+
+    #[test]
+    fn test_verify_by_did_dispatches_on_curve() {
+        let ed25519 = Ed25519Crypto::new();
+        let ed25519_did =
+            crate::did_key::bytes_to_did(KeyType::Ed25519, &ed25519.public_key()).unwrap();
+        let secp256k1 = Secp256k1Crypto::new();
+        let secp256k1_did =
+            crate::did_key::bytes_to_did(KeyType::Secp256k1, &secp256k1.public_key()).unwrap();
+
+        let data = b"commit bytes";
+        let ed25519_sig = ed25519.sign(data).unwrap();
+        let secp256k1_sig = secp256k1.sign(data).unwrap();
+
+        assert!(verify_by_did(data, &ed25519_sig, &ed25519_did).unwrap());
+        assert!(verify_by_did(data, &secp256k1_sig, &secp256k1_did).unwrap());
+
+        // A signature from the wrong curve's key must not verify.
+        assert!(!verify_by_did(data, &ed25519_sig, &secp256k1_did).unwrap());
+    }
 }
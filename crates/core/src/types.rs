@@ -36,28 +36,116 @@ impl fmt::Display for Did {
     }
 }
 
-/// CID (Content Identifier) - simplified version for ATProto
+/// CIDv1 header: version (1) ++ dag-cbor codec (0x71) ++ multihash code for
+/// sha2-256 (0x12) ++ digest length (0x20 = 32 bytes). All four values fit
+/// in a single byte each, so no varint encoding is needed.
+const CID_PREFIX: [u8; 4] = [0x01, 0x71, 0x12, 0x20];
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// RFC 4648 base32 (lowercase, unpadded) encoding, as used by multibase's
+/// `b` prefix.
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 8 + 4) / 5);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0u32;
+
+    for &byte in bytes {
+        bits = (bits << 8) | byte as u32;
+        bit_count += 8;
+        while bit_count >= 5 {
+            bit_count -= 5;
+            let index = (bits >> bit_count) & 0x1f;
+            out.push(BASE32_ALPHABET[index as usize] as char);
+        }
+    }
+    if bit_count > 0 {
+        let index = (bits << (5 - bit_count)) & 0x1f;
+        out.push(BASE32_ALPHABET[index as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(s: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 5 / 8);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0u32;
+
+    for c in s.chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&b| b as char == c)
+            .ok_or_else(|| Error::InvalidCid(format!("Invalid base32 character: {}", c)))?;
+        bits = (bits << 5) | value as u32;
+        bit_count += 5;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            out.push(((bits >> bit_count) & 0xff) as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// CID (Content Identifier) - CIDv1, DAG-CBOR codec, sha2-256 multihash,
+/// multibase base32 (lowercase, `b` prefix), matching what any other
+/// ATProto implementation computes for the same DAG-CBOR bytes.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Cid(String);
 
 impl Cid {
-    /// Create a new CID from raw bytes
+    /// Create a new CID by hashing `data` with sha2-256 and wrapping the
+    /// digest in a CIDv1/dag-cbor/sha2-256 multihash, encoded as multibase
+    /// base32. `data` must already be the canonical DAG-CBOR bytes being
+    /// addressed (see [`crate::dagcbor`]), not arbitrary bytes.
     pub fn from_bytes(data: &[u8]) -> Self {
         let mut hasher = Sha256::new();
         hasher.update(data);
-        let hash = hasher.finalize();
-        // Use base32 encoding for CID (simplified)
-        let hash_str = hex::encode(hash);
-        Cid(format!("bafyrei{}", &hash_str[..52]))
+        let digest = hasher.finalize();
+
+        let mut cid_bytes = Vec::with_capacity(CID_PREFIX.len() + digest.len());
+        cid_bytes.extend_from_slice(&CID_PREFIX);
+        cid_bytes.extend_from_slice(&digest);
+
+        Cid(format!("b{}", base32_encode(&cid_bytes)))
     }
 
-    /// Create a CID from a string
+    /// Create a CID from its multibase base32 string form, validating that
+    /// it decodes to a well-formed CIDv1/dag-cbor/sha2-256 multihash rather
+    /// than just checking a string prefix.
     pub fn from_string(cid: impl Into<String>) -> Result<Self> {
         let cid = cid.into();
-        if !cid.starts_with("bafy") {
+        Self::from_cid_bytes(&Self::decode_cid_bytes(&cid)?)?;
+        Ok(Cid(cid))
+    }
+
+    /// Construct a CID from its raw binary form (the multihash-structured
+    /// bytes, as found in a CAR block header), encoding it as the
+    /// canonical base32 string.
+    pub fn from_cid_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != CID_PREFIX.len() + 32 || bytes[..CID_PREFIX.len()] != CID_PREFIX {
+            return Err(Error::InvalidCid(format!(
+                "Not a CIDv1/dag-cbor/sha2-256 multihash: {} bytes",
+                bytes.len()
+            )));
+        }
+        Ok(Cid(format!("b{}", base32_encode(bytes))))
+    }
+
+    /// The raw binary (multihash-structured) form of this CID, as written
+    /// into a CAR block header rather than the base32 string.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Self::decode_cid_bytes(&self.0)
+    }
+
+    fn decode_cid_bytes(cid: &str) -> Result<Vec<u8>> {
+        let encoded = cid
+            .strip_prefix('b')
+            .ok_or_else(|| Error::InvalidCid(format!("Expected multibase base32 'b' prefix: {}", cid)))?;
+        let bytes = base32_decode(encoded)?;
+        if bytes.len() != CID_PREFIX.len() + 32 || bytes[..CID_PREFIX.len()] != CID_PREFIX {
             return Err(Error::InvalidCid(format!("Invalid CID format: {}", cid)));
         }
-        Ok(Cid(cid))
+        Ok(bytes)
     }
 
     /// Get the CID as a string slice
@@ -170,10 +258,11 @@ impl Record {
         format!("{}/{}", self.collection.as_str(), self.rkey.as_str())
     }
 
-    /// Compute the CID of this record
+    /// Compute the CID of this record, over its canonical DAG-CBOR encoding
+    /// so two implementations hash identical bytes for the same value.
     pub fn cid(&self) -> Result<Cid> {
-        let json = serde_json::to_vec(&self.value)?;
-        Ok(Cid::from_bytes(&json))
+        let bytes = crate::dagcbor::encode(&self.value);
+        Ok(Cid::from_bytes(&bytes))
     }
 }
 
@@ -203,6 +292,10 @@ pub struct Commit {
     pub record_cid: Option<Cid>,
     /// Previous commit CID (parent in the commit graph)
     pub prev: Option<Cid>,
+    /// Root CID of the Merkle Search Tree over the repository's full record
+    /// set as of this commit, so a remote reading the commit chain can
+    /// verify/sync the whole repo state without replaying every record.
+    pub mst_root: Option<Cid>,
     /// Timestamp of the commit
     pub timestamp: DateTime<Utc>,
     /// Signature over the commit data
@@ -226,23 +319,26 @@ impl Commit {
             rkey,
             record_cid,
             prev,
+            mst_root: None,
             timestamp: Utc::now(),
             signature: None,
         }
     }
 
-    /// Get the canonical bytes to sign
+    /// Get the canonical bytes to sign: the commit fields as canonical
+    /// DAG-CBOR, so two implementations sign (and verify) identical bytes.
     pub fn signing_bytes(&self) -> Result<Vec<u8>> {
-        let data = serde_json::to_vec(&serde_json::json!({
+        let value = serde_json::json!({
             "did": self.did.as_str(),
             "operation": self.operation,
             "collection": self.collection.as_str(),
             "rkey": self.rkey.as_str(),
             "record_cid": self.record_cid.as_ref().map(|c| c.as_str()),
             "prev": self.prev.as_ref().map(|c| c.as_str()),
+            "mst_root": self.mst_root.as_ref().map(|c| c.as_str()),
             "timestamp": self.timestamp.to_rfc3339(),
-        }))?;
-        Ok(data)
+        });
+        Ok(crate::dagcbor::encode(&value))
     }
 
     /// Compute the CID of this commit
@@ -286,7 +382,26 @@ mod tests {
     fn test_cid_creation() {
         let data = b"hello world";
         let cid = Cid::from_bytes(data);
-        assert!(cid.as_str().starts_with("bafy"));
+        assert!(cid.as_str().starts_with('b'));
+        assert_eq!(Cid::from_string(cid.as_str().to_string()).unwrap(), cid);
+    }
+
+    #[test]
+    fn test_cid_round_trips_through_binary_form() {
+        let cid = Cid::from_bytes(b"hello world");
+        let bytes = cid.to_bytes().unwrap();
+        assert_eq!(Cid::from_cid_bytes(&bytes).unwrap(), cid);
+    }
+
+    #[test]
+    fn test_cid_is_deterministic_for_same_bytes() {
+        assert_eq!(Cid::from_bytes(b"same"), Cid::from_bytes(b"same"));
+        assert_ne!(Cid::from_bytes(b"a"), Cid::from_bytes(b"b"));
+    }
+
+    #[test]
+    fn test_cid_rejects_malformed_string() {
+        assert!(Cid::from_string("not-a-cid".to_string()).is_err());
     }
 
     #[test]
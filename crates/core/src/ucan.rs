@@ -0,0 +1,239 @@
+//! UCAN (User-Controlled Authorization Network) capability tokens for
+//! delegated, scoped repo access — one DID can hand another a JWT granting
+//! just the actions it names, without sharing keys.
+//!
+//! A token is a standard three-segment JWT: a header naming the signing
+//! algorithm, a payload carrying the issuer/audience/expiry/capabilities,
+//! and a signature over the base64url-joined header and payload, produced
+//! by the issuer's own [`Crypto`] signer.
+
+use crate::did_key::{self, KeyType};
+use crate::error::{Error, Result};
+use crate::traits::{Clock, Crypto};
+use crate::types::Did;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One capability a token grants: permission to `can` (an action, e.g.
+/// `"create"`) against `with` (an NSID collection or a repo DID).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capability {
+    pub with: String,
+    pub can: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    typ: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Payload {
+    iss: String,
+    aud: String,
+    exp: i64,
+    att: Vec<Capability>,
+}
+
+fn alg_for(key_type: KeyType) -> &'static str {
+    match key_type {
+        KeyType::Ed25519 => "EdDSA",
+        KeyType::Secp256k1 => "ES256K",
+    }
+}
+
+/// Issue a UCAN JWT from `issuer`, delegating `capabilities` to `audience`
+/// until `expires`.
+pub fn issue(
+    issuer: &dyn Crypto,
+    audience: &Did,
+    capabilities: &[Capability],
+    expires: DateTime<Utc>,
+) -> Result<String> {
+    let iss = did_key::bytes_to_did(issuer.key_type(), &issuer.public_key())?;
+
+    let header = Header {
+        alg: alg_for(issuer.key_type()).to_string(),
+        typ: "JWT".to_string(),
+    };
+    let payload = Payload {
+        iss,
+        aud: audience.as_str().to_string(),
+        exp: expires.timestamp(),
+        att: capabilities.to_vec(),
+    };
+
+    let signing_input = encode_signing_input(&header, &payload)?;
+    let signature = issuer.sign(signing_input.as_bytes())?;
+
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+fn encode_signing_input(header: &Header, payload: &Payload) -> Result<String> {
+    let header_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_vec(header)?);
+    let payload_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_vec(payload)?);
+    Ok(format!("{}.{}", header_b64, payload_b64))
+}
+
+/// Verify `token`'s signature against its own `iss` did:key, check it
+/// hasn't expired (per `clock`), and confirm its capabilities cover
+/// `expected` — the `with`/`can` pair the caller is trying to authorize.
+pub fn verify(token: &str, expected: &Capability, clock: &dyn Clock) -> Result<bool> {
+    let mut parts = token.split('.');
+    let header_b64 = parts
+        .next()
+        .ok_or_else(|| Error::ValidationError("Malformed UCAN: missing header".to_string()))?;
+    let payload_b64 = parts
+        .next()
+        .ok_or_else(|| Error::ValidationError("Malformed UCAN: missing payload".to_string()))?;
+    let signature_b64 = parts
+        .next()
+        .ok_or_else(|| Error::ValidationError("Malformed UCAN: missing signature".to_string()))?;
+    if parts.next().is_some() {
+        return Err(Error::ValidationError(
+            "Malformed UCAN: too many segments".to_string(),
+        ));
+    }
+
+    let payload_bytes = URL_SAFE_NO_PAD
+        .decode(payload_b64)
+        .map_err(|e| Error::ValidationError(format!("Invalid UCAN payload encoding: {}", e)))?;
+    let payload: Payload = serde_json::from_slice(&payload_bytes)?;
+
+    if payload.exp < clock.now().timestamp() {
+        return Ok(false);
+    }
+
+    let covers = payload
+        .att
+        .iter()
+        .any(|cap| cap.with == expected.with && cap.can == expected.can);
+    if !covers {
+        return Ok(false);
+    }
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature_b64)
+        .map_err(|e| Error::ValidationError(format!("Invalid UCAN signature encoding: {}", e)))?;
+    let signing_input = format!("{}.{}", header_b64, payload_b64);
+
+    crate::traits::verify_by_did(signing_input.as_bytes(), &signature, &payload.iss)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{Ed25519Crypto, SystemClock};
+
+    #[test]
+    fn test_issue_and_verify_covered_capability() {
+        let issuer = Ed25519Crypto::new();
+        let audience = Did::new("did:plc:audience").unwrap();
+        let capabilities = vec![Capability {
+            with: "app.bsky.feed.post".to_string(),
+            can: "create".to_string(),
+        }];
+
+        let token = issue(
+            &issuer,
+            &audience,
+            &capabilities,
+            Utc::now() + chrono::Duration::hours(1),
+        )
+        .unwrap();
+
+        let granted = verify(
+            &token,
+            &Capability {
+                with: "app.bsky.feed.post".to_string(),
+                can: "create".to_string(),
+            },
+            &SystemClock,
+        )
+        .unwrap();
+        assert!(granted);
+    }
+
+    #[test]
+    fn test_verify_rejects_uncovered_capability() {
+        let issuer = Ed25519Crypto::new();
+        let audience = Did::new("did:plc:audience").unwrap();
+        let capabilities = vec![Capability {
+            with: "app.bsky.feed.post".to_string(),
+            can: "create".to_string(),
+        }];
+
+        let token = issue(
+            &issuer,
+            &audience,
+            &capabilities,
+            Utc::now() + chrono::Duration::hours(1),
+        )
+        .unwrap();
+
+        let granted = verify(
+            &token,
+            &Capability {
+                with: "app.bsky.feed.post".to_string(),
+                can: "delete".to_string(),
+            },
+            &SystemClock,
+        )
+        .unwrap();
+        assert!(!granted);
+    }
+
+    #[test]
+    fn test_verify_rejects_expired_token() {
+        let issuer = Ed25519Crypto::new();
+        let audience = Did::new("did:plc:audience").unwrap();
+        let capabilities = vec![Capability {
+            with: "app.bsky.feed.post".to_string(),
+            can: "create".to_string(),
+        }];
+
+        let token = issue(
+            &issuer,
+            &audience,
+            &capabilities,
+            Utc::now() - chrono::Duration::hours(1),
+        )
+        .unwrap();
+
+        let granted = verify(
+            &token,
+            &capabilities[0].clone(),
+            &SystemClock,
+        )
+        .unwrap();
+        assert!(!granted);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_signature() {
+        let issuer = Ed25519Crypto::new();
+        let audience = Did::new("did:plc:audience").unwrap();
+        let capabilities = vec![Capability {
+            with: "app.bsky.feed.post".to_string(),
+            can: "create".to_string(),
+        }];
+
+        let mut token = issue(
+            &issuer,
+            &audience,
+            &capabilities,
+            Utc::now() + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        token.push('a');
+
+        assert!(verify(&token, &capabilities[0].clone(), &SystemClock).is_err()
+            || !verify(&token, &capabilities[0].clone(), &SystemClock).unwrap());
+    }
+}
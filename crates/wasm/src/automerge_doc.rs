@@ -0,0 +1,99 @@
+//! WASM bindings for the standalone Automerge CRDT wrapper (`AutomergeDoc`)
+//!
+//! This is separate from `WasmRepository`, which stores records as plain
+//! JSON; `WasmAutomergeDoc` is for callers that want a raw mergeable
+//! document directly — e.g. a collaboratively-edited post body using the
+//! text/counter CRDT support in `pds_core::automerge_wrapper`.
+
+use pds_core::automerge_wrapper::AutomergeDoc;
+use wasm_bindgen::prelude::*;
+
+/// A mergeable CRDT document, exposed to JS.
+#[wasm_bindgen]
+pub struct WasmAutomergeDoc {
+    inner: AutomergeDoc,
+}
+
+#[wasm_bindgen]
+impl WasmAutomergeDoc {
+    /// Create a new empty document
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: AutomergeDoc::new(),
+        }
+    }
+
+    /// Load a document from bytes produced by `save`
+    pub fn load(bytes: Vec<u8>) -> std::result::Result<WasmAutomergeDoc, JsValue> {
+        let inner = AutomergeDoc::load(&bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to load document: {}", e)))?;
+        Ok(Self { inner })
+    }
+
+    /// Save the document to binary format
+    pub fn save(&self) -> Vec<u8> {
+        self.inner.save()
+    }
+
+    /// Get the current document state as a JSON string
+    pub fn to_json(&self) -> std::result::Result<String, JsValue> {
+        let value = self
+            .inner
+            .to_json()
+            .map_err(|e| JsValue::from_str(&format!("Failed to read document: {}", e)))?;
+        serde_json::to_string(&value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize: {}", e)))
+    }
+
+    /// Replace the document's top-level fields with `json`
+    pub fn update(&mut self, json: String) -> std::result::Result<(), JsValue> {
+        let value = serde_json::from_str(&json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid JSON: {}", e)))?;
+        self.inner
+            .update(&value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update document: {}", e)))
+    }
+
+    /// Merge `other` into this document, returning the resulting patches as
+    /// a JSON array so the caller can apply minimal incremental UI updates
+    /// instead of re-reading the whole document after every sync.
+    pub fn merge(&mut self, other: &mut WasmAutomergeDoc) -> std::result::Result<String, JsValue> {
+        let patches = self
+            .inner
+            .merge(&mut other.inner)
+            .map_err(|e| JsValue::from_str(&format!("Merge failed: {}", e)))?;
+        serde_json::to_string(&patches)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize patches: {}", e)))
+    }
+
+    /// List every change in the document as JSON (hash, actor, timestamp,
+    /// message, deps), for a UI to render history and let the user pick a
+    /// revision to pass to `checkout`.
+    pub fn history(&self) -> std::result::Result<String, JsValue> {
+        let history = self.inner.get_history();
+        serde_json::to_string(&history)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize history: {}", e)))
+    }
+
+    /// Reconstruct the document's JSON state as of `heads` (a JSON array of
+    /// hex-encoded change hashes, as found in `history()`), without
+    /// disturbing the live document — lets a user scrub through prior
+    /// versions of a post or profile.
+    pub fn checkout(&self, heads_json: String) -> std::result::Result<String, JsValue> {
+        let heads: Vec<automerge::ChangeHash> = serde_json::from_str(&heads_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid heads: {}", e)))?;
+        let value = self
+            .inner
+            .to_json_at(&heads)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read document at heads: {}", e)))?;
+        serde_json::to_string(&value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize: {}", e)))
+    }
+}
+
+impl Default for WasmAutomergeDoc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
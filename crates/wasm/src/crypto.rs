@@ -1,11 +1,11 @@
 //! Crypto implementation for WASM
 //!
-//! For now, we use the Ed25519 implementation from pds-core which works in WASM.
-//! A full WebCrypto implementation would be more complex and require different patterns
-//! due to async-only APIs and Send/Sync constraints.
+//! `WasmRepository` signs with [`WebCrypto`](crate::webcrypto::WebCrypto),
+//! which persists its keypair to `localStorage` and supports BIP39
+//! mnemonic backup — unlike `pds_core::traits::Ed25519Crypto`, which is
+//! just an in-memory keypair with no notion of persistence.
 
-// Re-export the Ed25519Crypto from core, which works fine in WASM
-pub use pds_core::traits::Ed25519Crypto as WasmCrypto;
+pub use crate::webcrypto::WebCrypto as WasmCrypto;
 
 #[cfg(test)]
 mod tests {
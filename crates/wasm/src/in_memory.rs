@@ -0,0 +1,161 @@
+//! In-memory key-value store for native tests and server-side rendering
+//!
+//! `storage::IndexedDbStore` hard-depends on `web_sys`/`window`, so nothing
+//! built on top of `KvStore` can run outside a browser. A `BTreeMap` gives
+//! ordered range scans for free and needs no JS runtime, so the record/commit
+//! layer can be exercised deterministically with plain `#[test]`s on native
+//! targets.
+
+use crate::storage::WriteOp;
+use pds_core::traits::KvStore;
+use pds_core::Result;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// `BTreeMap`-backed key-value store implementing the same [`KvStore`] trait
+/// as `storage::IndexedDbStore`'s cache.
+#[derive(Default)]
+pub struct InMemoryStore {
+    data: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a batch of puts/deletes as a single critical section, matching
+    /// `IndexedDbStore::apply_batch`'s all-or-nothing semantics.
+    pub fn apply_batch(&self, ops: Vec<WriteOp>) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        for op in ops {
+            match op {
+                WriteOp::Put(key, value) => {
+                    data.insert(key, value);
+                }
+                WriteOp::Delete(key) => {
+                    data.remove(&key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ordered range scan over `[prefix, prefix + "\u{ffff}")`, paginated by
+    /// `start_after`/`limit`. The `BTreeMap` keeps keys sorted, so this is a
+    /// plain `range()` walk rather than a full-table filter.
+    pub fn scan_prefix(
+        &self,
+        prefix: &str,
+        start_after: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        let upper = format!("{}\u{ffff}", prefix);
+        let lower = start_after.unwrap_or(prefix).to_string();
+
+        let data = self.data.lock().unwrap();
+        let mut results: Vec<(String, Vec<u8>)> = data
+            .range(lower..upper)
+            .filter(|(key, _)| Some(key.as_str()) != start_after)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        if let Some(limit) = limit {
+            results.truncate(limit);
+        }
+
+        Ok(results)
+    }
+}
+
+impl KvStore for InMemoryStore {
+    fn put(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        self.data.lock().unwrap().insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.data.lock().unwrap().get(key).cloned())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<()> {
+        self.data.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.data.lock().unwrap().contains_key(key))
+    }
+
+    fn list_keys(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|k| k.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_get_delete() {
+        let mut store = InMemoryStore::new();
+        store.put("records/a", b"one").unwrap();
+        assert_eq!(store.get("records/a").unwrap(), Some(b"one".to_vec()));
+
+        store.delete("records/a").unwrap();
+        assert_eq!(store.get("records/a").unwrap(), None);
+    }
+
+    #[test]
+    fn test_list_keys_by_prefix() {
+        let mut store = InMemoryStore::new();
+        store.put("records/app.bsky.feed.post/1", b"a").unwrap();
+        store.put("records/app.bsky.feed.post/2", b"b").unwrap();
+        store.put("identity", b"did:plc:test").unwrap();
+
+        let keys = store.list_keys("records/app.bsky.feed.post/").unwrap();
+        assert_eq!(keys.len(), 2);
+    }
+
+    #[test]
+    fn test_scan_prefix_pagination() {
+        let store = InMemoryStore::new();
+        for i in 0..5 {
+            store
+                .apply_batch(vec![WriteOp::Put(format!("records/col/{}", i), vec![i as u8])])
+                .unwrap();
+        }
+
+        let page1 = store.scan_prefix("records/col/", None, Some(2)).unwrap();
+        assert_eq!(page1.len(), 2);
+
+        let last_key = &page1.last().unwrap().0;
+        let page2 = store
+            .scan_prefix("records/col/", Some(last_key), Some(2))
+            .unwrap();
+        assert_eq!(page2.len(), 2);
+        assert_ne!(page1[0].0, page2[0].0);
+    }
+
+    #[test]
+    fn test_apply_batch_is_all_or_nothing_in_ordering() {
+        let store = InMemoryStore::new();
+        store
+            .apply_batch(vec![
+                WriteOp::Put("records/a".to_string(), b"1".to_vec()),
+                WriteOp::Put("records/b".to_string(), b"2".to_vec()),
+                WriteOp::Delete("records/a".to_string()),
+            ])
+            .unwrap();
+
+        assert_eq!(store.get("records/a").unwrap(), None);
+        assert_eq!(store.get("records/b").unwrap(), Some(b"2".to_vec()));
+    }
+}
@@ -6,17 +6,22 @@
 //! the repository to run entirely in the browser with IndexedDB persistence
 //! and WebCrypto signing.
 
+mod automerge_doc;
 mod clock;
 mod crypto;
 mod error;
+mod in_memory;
 mod storage;
+mod webcrypto;
 
 use wasm_bindgen::prelude::*;
 
+pub use automerge_doc::WasmAutomergeDoc;
 pub use clock::JsClock;
 pub use crypto::WasmCrypto;
 pub use error::{Result, WasmError};
-pub use storage::IndexedDbStore;
+pub use in_memory::InMemoryStore;
+pub use storage::{Column, IndexedDbStore, WriteOp};
 
 // Re-export for convenience
 use pds_core::{
@@ -67,8 +72,10 @@ impl WasmRepository {
             .await
             .map_err(|e| JsValue::from_str(&format!("Storage init failed: {}", e)))?;
 
-        // Create crypto
-        let crypto = WasmCrypto::new();
+        // Create crypto, restoring the previously persisted keypair if one
+        // exists so the identity survives a page reload
+        let crypto = WasmCrypto::load_or_create()
+            .map_err(|e| JsValue::from_str(&format!("Crypto init failed: {}", e)))?;
 
         // Create repository
         let clock = JsClock::new();
@@ -209,6 +216,56 @@ impl WasmRepository {
             .map_err(|e| JsValue::from_str(&format!("Failed to serialize snapshot: {}", e)))
     }
 
+    /// Export repository snapshot as a CAR v1 byte stream for ATProto sync
+    /// interop (e.g. `com.atproto.sync.getRepo`)
+    #[wasm_bindgen]
+    pub fn export_car(&self) -> std::result::Result<Vec<u8>, JsValue> {
+        let repo = self
+            .repo
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("Repository not initialized"))?;
+
+        let snapshot = pds_core::snapshot::Snapshot::from_repo(repo)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create snapshot: {}", e)))?;
+
+        snapshot
+            .to_car()
+            .map_err(|e| JsValue::from_str(&format!("Failed to encode CAR: {}", e)))
+    }
+
+    /// Restore repository from a CAR v1 byte stream produced by
+    /// `export_car`. `public_key` is the signer's Ed25519 public key (e.g.
+    /// resolved from their DID document), checked against every commit in
+    /// the chain before any record is replayed.
+    #[wasm_bindgen]
+    pub async fn import_car(
+        &mut self,
+        bytes: Vec<u8>,
+        public_key: Vec<u8>,
+    ) -> std::result::Result<(), JsValue> {
+        let snapshot = pds_core::snapshot::Snapshot::from_car(&bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to decode CAR: {}", e)))?;
+
+        // Re-initialize with the DID from the CAR file's commits
+        self.init_identity(snapshot.did.clone()).await?;
+
+        let repo = self
+            .repo
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("Repository not initialized"))?;
+        repo.restore_from_snapshot(&snapshot, &public_key)
+            .map_err(|e| JsValue::from_str(&format!("Failed to restore from CAR: {}", e)))?;
+
+        if let Some(store) = self.store.as_mut() {
+            store
+                .flush()
+                .await
+                .map_err(|e| JsValue::from_str(&format!("Failed to flush: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
     /// Create a backup of the repository
     /// Returns JSON string of the backup
     #[wasm_bindgen]
@@ -216,10 +273,15 @@ impl WasmRepository {
         self.export_for_publish()
     }
 
-    /// Restore repository from a backup
-    /// Takes a JSON string of the backup
+    /// Restore repository from a backup. Takes a JSON string of the backup
+    /// and the signer's Ed25519 public key, used to verify every commit in
+    /// the chain before any record is replayed.
     #[wasm_bindgen]
-    pub async fn restore(&mut self, backup_json: String) -> std::result::Result<(), JsValue> {
+    pub async fn restore(
+        &mut self,
+        backup_json: String,
+        public_key: Vec<u8>,
+    ) -> std::result::Result<(), JsValue> {
         // Parse the snapshot
         let snapshot = pds_core::snapshot::Snapshot::from_json(&backup_json)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse backup: {}", e)))?;
@@ -227,8 +289,19 @@ impl WasmRepository {
         // Re-initialize with the DID from backup
         self.init_identity(snapshot.did.clone()).await?;
 
-        // TODO: Restore records and commits from snapshot
-        // This would involve iterating through the snapshot and recreating records
+        let repo = self
+            .repo
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("Repository not initialized"))?;
+        repo.restore_from_snapshot(&snapshot, &public_key)
+            .map_err(|e| JsValue::from_str(&format!("Failed to restore from backup: {}", e)))?;
+
+        if let Some(store) = self.store.as_mut() {
+            store
+                .flush()
+                .await
+                .map_err(|e| JsValue::from_str(&format!("Failed to flush: {}", e)))?;
+        }
 
         Ok(())
     }
@@ -239,6 +312,22 @@ impl WasmRepository {
         self.did.as_ref().map(|d| d.to_string())
     }
 
+    /// Get the root CID of the Merkle Search Tree over the current record
+    /// set, or `None` if the repository has no records yet
+    #[wasm_bindgen]
+    pub fn get_repo_root(&self) -> std::result::Result<Option<String>, JsValue> {
+        let repo = self
+            .repo
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("Repository not initialized"))?;
+
+        let root = repo
+            .mst_root()
+            .map_err(|e| JsValue::from_str(&format!("Failed to compute MST root: {}", e)))?;
+
+        Ok(root.map(|cid| cid.to_string()))
+    }
+
     /// Get the public key as base64
     #[wasm_bindgen]
     pub fn get_public_key(&self) -> Option<String> {
@@ -1,80 +1,616 @@
 //! KvStore implementation using IndexedDB
+//!
+//! Reads and writes go through an in-memory cache so `Repository`'s
+//! synchronous `KvStore` bound keeps working without every call touching the
+//! browser's async storage API. `init()` opens (or upgrades) the IndexedDB
+//! database and hydrates the cache from whatever is already there; `flush()`
+//! persists every key written since the last flush back to IndexedDB in one
+//! batched transaction. `AsyncKvStore` is also implemented directly against
+//! IndexedDB, for callers that want a real round trip per operation instead
+//! of the cache/flush pair.
+//!
+//! `apply_batch`/`scan_prefix` and the [`Column`] views on top of them all
+//! operate against the same cache `put`/`get`/`delete` do, so a key written
+//! through one is visible to the others without a separate storage layer to
+//! keep in sync.
 
-use crate::error::Result;
-use pds_core::traits::KvStore;
-use std::collections::HashMap;
+use crate::error::{Result as WasmResult, WasmError};
+use pds_core::records::keys;
+use pds_core::traits::{AsyncKvStore, KvStore};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Event, IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransaction, IdbTransactionMode};
+use js_sys::{Array, Promise, Uint8Array};
+
+const DB_VERSION: u32 = 1;
+const STORE_NAME: &str = "kvstore";
+
+/// Shared mutable state behind every clone of an [`IndexedDbStore`]. The
+/// store is cloned at least twice per repository (once into the
+/// `Repository` it's handed to, once kept directly by `WasmRepository` for
+/// `flush()`), so the cache has to live behind a handle those clones share
+/// rather than one each gets its own copy of — otherwise a `flush()` called
+/// through one clone would see none of the writes made through another.
+#[derive(Default)]
+struct Inner {
+    cache: BTreeMap<String, Vec<u8>>,
+    dirty_keys: HashSet<String>,
+}
+
+/// A single mutation to apply as part of a batch write.
+#[derive(Debug, Clone)]
+pub enum WriteOp {
+    Put(String, Vec<u8>),
+    Delete(String),
+}
+
+/// A logical grouping of keys sharing one of `records::keys`'s top-level
+/// prefixes, so callers needing per-kind scans/clears (export, selective
+/// pruning) don't have to hand-roll prefix matching against the shared
+/// keyspace every time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Identity,
+    Commits,
+    Records,
+}
+
+impl Column {
+    const ALL: [Column; 3] = [Column::Identity, Column::Commits, Column::Records];
+
+    fn matches(self, key: &str) -> bool {
+        match self {
+            Column::Identity => key == keys::IDENTITY_KEY,
+            Column::Commits => key.starts_with(keys::COMMITS_PREFIX),
+            Column::Records => key.starts_with(keys::RECORDS_PREFIX),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Column::Identity => "identity",
+            Column::Commits => "commits",
+            Column::Records => "records",
+        }
+    }
+}
 
 /// IndexedDB-backed key-value store for browser persistence
 #[derive(Clone)]
 pub struct IndexedDbStore {
-    #[allow(dead_code)]
     db_name: String,
-    // In-memory cache for synchronous API compatibility
-    cache: HashMap<String, Vec<u8>>,
-    // Flag to track if we need to flush to IndexedDB
-    dirty: bool,
+    inner: Arc<Mutex<Inner>>,
 }
 
 impl IndexedDbStore {
-    /// Create a new IndexedDB store
+    /// Create a new IndexedDB store. Call [`init`](Self::init) before use.
     pub fn new(db_name: impl Into<String>) -> Self {
         Self {
             db_name: db_name.into(),
-            cache: HashMap::new(),
-            dirty: false,
+            inner: Arc::new(Mutex::new(Inner::default())),
         }
     }
 
-    /// Initialize the IndexedDB database
-    pub async fn init(&mut self) -> Result<()> {
-        // For now, just initialize the cache
-        // Full IndexedDB implementation would be complex and is beyond the minimal scope
-        // The cache-based approach works for testing and initial implementation
+    /// Open (creating or upgrading as needed) the named IndexedDB database
+    /// and hydrate the in-memory cache from its current contents, so data
+    /// written in a previous browser session is visible immediately.
+    pub async fn init(&mut self) -> WasmResult<()> {
+        let db = Self::open_db(&self.db_name).await?;
+        let entries = Self::scan_all(&db).await?;
+
+        let mut inner = self.inner.lock().unwrap();
+        for (key, value) in entries {
+            inner.cache.insert(key, value);
+        }
         Ok(())
     }
 
-    /// Flush dirty cache entries to IndexedDB
-    pub async fn flush(&mut self) -> Result<()> {
-        if !self.dirty {
+    /// Persist every key written since the last flush to IndexedDB, as a
+    /// single batched readwrite transaction, then clear the dirty set.
+    pub async fn flush(&mut self) -> WasmResult<()> {
+        let (dirty, snapshot) = {
+            let inner = self.inner.lock().unwrap();
+            let dirty: Vec<String> = inner.dirty_keys.iter().cloned().collect();
+            (dirty, inner.cache.clone())
+        };
+        if dirty.is_empty() {
             return Ok(());
         }
 
-        // Simplified implementation - in production this would write to IndexedDB
-        // For now, we keep everything in memory cache
-        self.dirty = false;
+        let db = Self::open_db(&self.db_name).await?;
+        let transaction = Self::readwrite_transaction(&db)?;
+        let store = Self::object_store(&transaction)?;
+
+        for key in &dirty {
+            match snapshot.get(key) {
+                Some(value) => {
+                    let array = Uint8Array::new_with_length(value.len() as u32);
+                    array.copy_from(value);
+                    store
+                        .put_with_key(&array, &JsValue::from_str(key))
+                        .map_err(|e| WasmError::Storage(format!("Failed to queue put: {:?}", e)))?;
+                }
+                None => {
+                    store
+                        .delete(&JsValue::from_str(key))
+                        .map_err(|e| WasmError::Storage(format!("Failed to queue delete: {:?}", e)))?;
+                }
+            }
+        }
+
+        JsFuture::from(Self::transaction_to_promise(&transaction))
+            .await
+            .map_err(|e| WasmError::Storage(format!("Flush transaction failed: {:?}", e)))?;
+
+        // Only clear the keys once the transaction has committed, so a
+        // failed flush leaves them dirty for the next attempt.
+        let mut inner = self.inner.lock().unwrap();
+        for key in &dirty {
+            inner.dirty_keys.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Apply a batch of puts/deletes against the shared cache as a single
+    /// critical section, marking every touched key dirty so the next
+    /// `flush()` persists the whole batch together.
+    pub fn apply_batch(&self, ops: Vec<WriteOp>) -> pds_core::Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        for op in ops {
+            match op {
+                WriteOp::Put(key, value) => {
+                    inner.dirty_keys.insert(key.clone());
+                    inner.cache.insert(key, value);
+                }
+                WriteOp::Delete(key) => {
+                    inner.cache.remove(&key);
+                    inner.dirty_keys.insert(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ordered range scan over `[prefix, prefix + "\u{ffff}")` of the shared
+    /// cache, paginated by `start_after`/`limit` — the same scheme
+    /// `InMemoryStore::scan_prefix` uses, so native and browser builds page
+    /// through a collection identically.
+    pub fn scan_prefix(
+        &self,
+        prefix: &str,
+        start_after: Option<&str>,
+        limit: Option<usize>,
+    ) -> pds_core::Result<Vec<(String, Vec<u8>)>> {
+        let upper = format!("{}\u{ffff}", prefix);
+        let lower = start_after.unwrap_or(prefix).to_string();
+
+        let inner = self.inner.lock().unwrap();
+        let mut results: Vec<(String, Vec<u8>)> = inner
+            .cache
+            .range(lower..upper)
+            .filter(|(key, _)| Some(key.as_str()) != start_after)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        if let Some(limit) = limit {
+            results.truncate(limit);
+        }
+
+        Ok(results)
+    }
+
+    /// Get a handle scoped to a single [`Column`], so scans/clears can't
+    /// cross into another column's keys.
+    pub fn column(&self, column: Column) -> ColumnHandle<'_> {
+        ColumnHandle {
+            store: self,
+            column,
+        }
+    }
+
+    /// Dump every key/value pair across all columns into a single
+    /// self-describing archive, so a cleared browser's data can be restored
+    /// via [`import`](Self::import) on another browser (account portability)
+    /// or kept as a debug snapshot.
+    ///
+    /// Framing: `MAGIC | column_name_len: u16 | column_name |
+    /// entry_count: u32 | (key_len: u32 | key | value_len: u32 | value)*`
+    /// repeated per column.
+    pub fn export(&self) -> pds_core::Result<Vec<u8>> {
+        const MAGIC: &[u8] = b"PDSX1";
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+
+        for column in Column::ALL {
+            let entries = self.column(column).scan_prefix("", None, None)?;
+
+            let name = column.name().as_bytes();
+            buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            buf.extend_from_slice(name);
+            buf.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+            for (key, value) in entries {
+                let key_bytes = key.as_bytes();
+                buf.extend_from_slice(&(key_bytes.len() as u32).to_le_bytes());
+                buf.extend_from_slice(key_bytes);
+                buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                buf.extend_from_slice(&value);
+            }
+        }
+
+        Ok(buf)
+    }
+
+    /// Restore an archive produced by [`export`](Self::export), replaying
+    /// each column's entries through one [`apply_batch`](Self::apply_batch)
+    /// call so a partial import can't leave a column half-populated.
+    pub fn import(&self, blob: &[u8]) -> pds_core::Result<()> {
+        const MAGIC: &[u8] = b"PDSX1";
+        if !blob.starts_with(MAGIC) {
+            return Err(to_core_err(WasmError::Storage(
+                "Not a valid PDS export archive".to_string(),
+            )));
+        }
+        let mut cursor = MAGIC.len();
+
+        let read_u16 = |buf: &[u8], at: usize| -> pds_core::Result<u16> {
+            buf.get(at..at + 2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .ok_or_else(|| to_core_err(WasmError::Storage("Truncated export archive".to_string())))
+        };
+        let read_u32 = |buf: &[u8], at: usize| -> pds_core::Result<u32> {
+            buf.get(at..at + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or_else(|| to_core_err(WasmError::Storage("Truncated export archive".to_string())))
+        };
+        let read_slice = |buf: &[u8], at: usize, len: usize| -> pds_core::Result<&[u8]> {
+            buf.get(at..at + len)
+                .ok_or_else(|| to_core_err(WasmError::Storage("Truncated export archive".to_string())))
+        };
+
+        while cursor < blob.len() {
+            let name_len = read_u16(blob, cursor)? as usize;
+            cursor += 2;
+            let name = std::str::from_utf8(read_slice(blob, cursor, name_len)?)
+                .map_err(|e| to_core_err(WasmError::Storage(format!("Invalid column name: {}", e))))?
+                .to_string();
+            cursor += name_len;
+
+            if !Column::ALL.into_iter().any(|c| c.name() == name) {
+                return Err(to_core_err(WasmError::Storage(format!(
+                    "Unknown column in archive: {}",
+                    name
+                ))));
+            }
+
+            let entry_count = read_u32(blob, cursor)?;
+            cursor += 4;
+
+            let mut ops = Vec::with_capacity(entry_count as usize);
+            for _ in 0..entry_count {
+                let key_len = read_u32(blob, cursor)? as usize;
+                cursor += 4;
+                let key = std::str::from_utf8(read_slice(blob, cursor, key_len)?)
+                    .map_err(|e| to_core_err(WasmError::Storage(format!("Invalid key: {}", e))))?
+                    .to_string();
+                cursor += key_len;
+
+                let value_len = read_u32(blob, cursor)? as usize;
+                cursor += 4;
+                let value = read_slice(blob, cursor, value_len)?.to_vec();
+                cursor += value_len;
+
+                ops.push(WriteOp::Put(key, value));
+            }
+
+            self.apply_batch(ops)?;
+        }
+
         Ok(())
     }
+
+    /// Open the database by name, creating the single `kvstore` object store
+    /// on first use or on a version bump.
+    async fn open_db(db_name: &str) -> WasmResult<IdbDatabase> {
+        let window = window().ok_or_else(|| WasmError::Storage("No window object".to_string()))?;
+        let factory = window
+            .indexed_db()
+            .map_err(|e| WasmError::Storage(format!("Failed to get IndexedDB: {:?}", e)))?
+            .ok_or_else(|| WasmError::Storage("IndexedDB not available".to_string()))?;
+
+        let open_request = factory
+            .open_with_u32(db_name, DB_VERSION)
+            .map_err(|e| WasmError::Storage(format!("Failed to open database: {:?}", e)))?;
+
+        let onupgradeneeded = Closure::wrap(Box::new(move |event: Event| {
+            let target = event.target().unwrap();
+            let request = target.dyn_ref::<IdbOpenDbRequest>().unwrap();
+            let db = request.result().unwrap().dyn_into::<IdbDatabase>().unwrap();
+            // Fails silently if the store already exists from a prior open.
+            let _ = db.create_object_store(STORE_NAME);
+        }) as Box<dyn FnMut(_)>);
+        open_request.set_onupgradeneeded(Some(onupgradeneeded.as_ref().unchecked_ref()));
+        onupgradeneeded.forget();
+
+        let result = JsFuture::from(Self::request_to_promise(&open_request))
+            .await
+            .map_err(|e| WasmError::Storage(format!("Failed to open database: {:?}", e)))?;
+
+        result
+            .dyn_into::<IdbDatabase>()
+            .map_err(|_| WasmError::Storage("Invalid database object".to_string()))
+    }
+
+    fn readwrite_transaction(db: &IdbDatabase) -> WasmResult<IdbTransaction> {
+        db.transaction_with_str_and_mode(STORE_NAME, IdbTransactionMode::Readwrite)
+            .map_err(|e| WasmError::Storage(format!("Failed to open transaction: {:?}", e)))
+    }
+
+    fn readonly_transaction(db: &IdbDatabase) -> WasmResult<IdbTransaction> {
+        db.transaction_with_str_and_mode(STORE_NAME, IdbTransactionMode::Readonly)
+            .map_err(|e| WasmError::Storage(format!("Failed to open transaction: {:?}", e)))
+    }
+
+    fn object_store(transaction: &IdbTransaction) -> WasmResult<web_sys::IdbObjectStore> {
+        transaction
+            .object_store(STORE_NAME)
+            .map_err(|e| WasmError::Storage(format!("Failed to get object store: {:?}", e)))
+    }
+
+    /// Range-scan every key/value pair currently in the object store, used
+    /// both to hydrate the cache on `init()` and to serve `list_keys`.
+    async fn scan_all(db: &IdbDatabase) -> WasmResult<Vec<(String, Vec<u8>)>> {
+        let transaction = Self::readonly_transaction(db)?;
+        let store = Self::object_store(&transaction)?;
+
+        let keys_request = store
+            .get_all_keys()
+            .map_err(|e| WasmError::Storage(format!("Failed to list keys: {:?}", e)))?;
+        let keys_result = JsFuture::from(Self::request_to_promise(&keys_request))
+            .await
+            .map_err(|e| WasmError::Storage(format!("List keys failed: {:?}", e)))?;
+
+        let values_request = store
+            .get_all()
+            .map_err(|e| WasmError::Storage(format!("Failed to list values: {:?}", e)))?;
+        let values_result = JsFuture::from(Self::request_to_promise(&values_request))
+            .await
+            .map_err(|e| WasmError::Storage(format!("List values failed: {:?}", e)))?;
+
+        let keys = Array::from(&keys_result);
+        let values = Array::from(&values_result);
+
+        let mut entries = Vec::with_capacity(keys.length() as usize);
+        for i in 0..keys.length() {
+            let key = keys
+                .get(i)
+                .as_string()
+                .ok_or_else(|| WasmError::Storage("Non-string IndexedDB key".to_string()))?;
+            let array = Uint8Array::new(&values.get(i));
+            let mut bytes = vec![0u8; array.length() as usize];
+            array.copy_to(&mut bytes);
+            entries.push((key, bytes));
+        }
+        Ok(entries)
+    }
+
+    /// Wrap an `IdbRequest` in a `Promise` that resolves with its result on
+    /// `onsuccess` and rejects on `onerror`.
+    fn request_to_promise(request: &IdbRequest) -> Promise {
+        Promise::new(&mut |resolve, reject| {
+            let onsuccess = Closure::wrap(Box::new(move |event: Event| {
+                let target = event.target().unwrap();
+                let request = target.dyn_ref::<IdbRequest>().unwrap();
+                let result = request.result().unwrap();
+                resolve.call1(&JsValue::NULL, &result).unwrap();
+            }) as Box<dyn FnMut(_)>);
+            let onerror = Closure::wrap(Box::new(move |_event: Event| {
+                reject.call0(&JsValue::NULL).unwrap();
+            }) as Box<dyn FnMut(_)>);
+
+            request.set_onsuccess(Some(onsuccess.as_ref().unchecked_ref()));
+            request.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+            onsuccess.forget();
+            onerror.forget();
+        })
+    }
+
+    /// Wrap an `IdbTransaction` in a `Promise` that resolves on
+    /// `oncomplete`, so callers await the whole batch rather than its
+    /// individual requests.
+    fn transaction_to_promise(transaction: &IdbTransaction) -> Promise {
+        Promise::new(&mut |resolve, reject| {
+            let oncomplete = Closure::wrap(Box::new(move |_event: Event| {
+                resolve.call0(&JsValue::NULL).unwrap();
+            }) as Box<dyn FnMut(_)>);
+            let onerror = Closure::wrap(Box::new(move |_event: Event| {
+                reject.call0(&JsValue::NULL).unwrap();
+            }) as Box<dyn FnMut(_)>);
+
+            transaction.set_oncomplete(Some(oncomplete.as_ref().unchecked_ref()));
+            transaction.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+            oncomplete.forget();
+            onerror.forget();
+        })
+    }
 }
 
 impl KvStore for IndexedDbStore {
     fn put(&mut self, key: &str, value: &[u8]) -> pds_core::Result<()> {
-        self.cache.insert(key.to_string(), value.to_vec());
-        self.dirty = true;
+        let mut inner = self.inner.lock().unwrap();
+        inner.cache.insert(key.to_string(), value.to_vec());
+        inner.dirty_keys.insert(key.to_string());
         Ok(())
     }
 
     fn get(&self, key: &str) -> pds_core::Result<Option<Vec<u8>>> {
-        Ok(self.cache.get(key).cloned())
+        Ok(self.inner.lock().unwrap().cache.get(key).cloned())
     }
 
     fn delete(&mut self, key: &str) -> pds_core::Result<()> {
-        self.cache.remove(key);
-        self.dirty = true;
+        let mut inner = self.inner.lock().unwrap();
+        inner.cache.remove(key);
+        inner.dirty_keys.insert(key.to_string());
         Ok(())
     }
 
     fn exists(&self, key: &str) -> pds_core::Result<bool> {
-        Ok(self.cache.contains_key(key))
+        Ok(self.inner.lock().unwrap().cache.contains_key(key))
     }
 
     fn list_keys(&self, prefix: &str) -> pds_core::Result<Vec<String>> {
-        let keys: Vec<String> = self
+        Ok(self
+            .inner
+            .lock()
+            .unwrap()
             .cache
             .keys()
             .filter(|k| k.starts_with(prefix))
             .cloned()
+            .collect())
+    }
+}
+
+/// Direct, uncached IndexedDB access — each call is its own transaction
+/// against the real store, for callers that need a genuine round trip
+/// instead of the synchronous cache/flush pair above.
+#[async_trait::async_trait(?Send)]
+impl AsyncKvStore for IndexedDbStore {
+    async fn put(&self, key: &str, value: &[u8]) -> pds_core::Result<()> {
+        let db = Self::open_db(&self.db_name).await.map_err(to_core_err)?;
+        let transaction = Self::readwrite_transaction(&db).map_err(to_core_err)?;
+        let store = Self::object_store(&transaction).map_err(to_core_err)?;
+
+        let array = Uint8Array::new_with_length(value.len() as u32);
+        array.copy_from(value);
+        store
+            .put_with_key(&array, &JsValue::from_str(key))
+            .map_err(|e| to_core_err(WasmError::Storage(format!("Failed to put value: {:?}", e))))?;
+
+        JsFuture::from(Self::transaction_to_promise(&transaction))
+            .await
+            .map_err(|e| to_core_err(WasmError::Storage(format!("Put transaction failed: {:?}", e))))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> pds_core::Result<Option<Vec<u8>>> {
+        let db = Self::open_db(&self.db_name).await.map_err(to_core_err)?;
+        let transaction = Self::readonly_transaction(&db).map_err(to_core_err)?;
+        let store = Self::object_store(&transaction).map_err(to_core_err)?;
+
+        let request = store
+            .get(&JsValue::from_str(key))
+            .map_err(|e| to_core_err(WasmError::Storage(format!("Failed to get value: {:?}", e))))?;
+        let result = JsFuture::from(Self::request_to_promise(&request))
+            .await
+            .map_err(|e| to_core_err(WasmError::Storage(format!("Get operation failed: {:?}", e))))?;
+
+        if result.is_undefined() || result.is_null() {
+            return Ok(None);
+        }
+        let array = Uint8Array::new(&result);
+        let mut bytes = vec![0u8; array.length() as usize];
+        array.copy_to(&mut bytes);
+        Ok(Some(bytes))
+    }
+
+    async fn delete(&self, key: &str) -> pds_core::Result<()> {
+        let db = Self::open_db(&self.db_name).await.map_err(to_core_err)?;
+        let transaction = Self::readwrite_transaction(&db).map_err(to_core_err)?;
+        let store = Self::object_store(&transaction).map_err(to_core_err)?;
+
+        store
+            .delete(&JsValue::from_str(key))
+            .map_err(|e| to_core_err(WasmError::Storage(format!("Failed to delete value: {:?}", e))))?;
+
+        JsFuture::from(Self::transaction_to_promise(&transaction))
+            .await
+            .map_err(|e| to_core_err(WasmError::Storage(format!("Delete transaction failed: {:?}", e))))?;
+        Ok(())
+    }
+
+    async fn list_keys(&self, prefix: &str) -> pds_core::Result<Vec<String>> {
+        let db = Self::open_db(&self.db_name).await.map_err(to_core_err)?;
+        let entries = Self::scan_all(&db).await.map_err(to_core_err)?;
+        Ok(entries
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.starts_with(prefix))
+            .collect())
+    }
+}
+
+fn to_core_err(err: WasmError) -> pds_core::Error {
+    pds_core::Error::StorageError(err.to_string())
+}
+
+/// A view of an [`IndexedDbStore`] scoped to a single [`Column`], so scans,
+/// batches, and clears can't cross into another column's keys.
+pub struct ColumnHandle<'a> {
+    store: &'a IndexedDbStore,
+    column: Column,
+}
+
+impl ColumnHandle<'_> {
+    pub fn get(&self, key: &str) -> pds_core::Result<Option<Vec<u8>>> {
+        if !self.column.matches(key) {
+            return Ok(None);
+        }
+        self.store.get(key)
+    }
+
+    pub fn set(&self, key: &str, value: Vec<u8>) -> pds_core::Result<()> {
+        self.apply_batch(vec![WriteOp::Put(key.to_string(), value)])
+    }
+
+    pub fn delete(&self, key: &str) -> pds_core::Result<()> {
+        self.apply_batch(vec![WriteOp::Delete(key.to_string())])
+    }
+
+    pub fn scan_prefix(
+        &self,
+        prefix: &str,
+        start_after: Option<&str>,
+        limit: Option<usize>,
+    ) -> pds_core::Result<Vec<(String, Vec<u8>)>> {
+        let column = self.column;
+        let mut entries: Vec<_> = self
+            .store
+            .scan_prefix(prefix, start_after, None)?
+            .into_iter()
+            .filter(|(key, _)| column.matches(key))
             .collect();
-        Ok(keys)
+        if let Some(limit) = limit {
+            entries.truncate(limit);
+        }
+        Ok(entries)
+    }
+
+    pub fn apply_batch(&self, ops: Vec<WriteOp>) -> pds_core::Result<()> {
+        for op in &ops {
+            let key = match op {
+                WriteOp::Put(key, _) | WriteOp::Delete(key) => key,
+            };
+            if !self.column.matches(key) {
+                return Err(to_core_err(WasmError::Storage(format!(
+                    "Key {} is outside the {} column",
+                    key,
+                    self.column.name()
+                ))));
+            }
+        }
+        self.store.apply_batch(ops)
+    }
+
+    pub fn clear(&self) -> pds_core::Result<()> {
+        let keys: Vec<String> = self
+            .scan_prefix("", None, None)?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        self.apply_batch(keys.into_iter().map(WriteOp::Delete).collect())
     }
 }
 
@@ -105,4 +641,96 @@ mod tests {
         store.delete("key1").unwrap();
         assert!(!store.exists("key1").unwrap());
     }
+
+    #[test]
+    fn test_clones_share_cache() {
+        // A clone (as `Repository` and `WasmRepository` each hold one) must
+        // see writes made through the other clone, or `flush()` would
+        // persist a stale, empty copy instead of the real data.
+        let mut store = IndexedDbStore::new("test_db");
+        let clone = store.clone();
+
+        store.put("key1", b"value1").unwrap();
+
+        assert_eq!(clone.get("key1").unwrap(), Some(b"value1".to_vec()));
+    }
+
+    #[test]
+    fn test_apply_batch_is_all_or_nothing_in_ordering() {
+        let store = IndexedDbStore::new("test_db");
+        store
+            .apply_batch(vec![
+                WriteOp::Put("records/a".to_string(), b"1".to_vec()),
+                WriteOp::Put("records/b".to_string(), b"2".to_vec()),
+                WriteOp::Delete("records/a".to_string()),
+            ])
+            .unwrap();
+
+        assert_eq!(store.get("records/a").unwrap(), None);
+        assert_eq!(store.get("records/b").unwrap(), Some(b"2".to_vec()));
+    }
+
+    #[test]
+    fn test_scan_prefix_pagination() {
+        let store = IndexedDbStore::new("test_db");
+        for i in 0..5 {
+            store
+                .apply_batch(vec![WriteOp::Put(format!("records/col/{}", i), vec![i as u8])])
+                .unwrap();
+        }
+
+        let page1 = store.scan_prefix("records/col/", None, Some(2)).unwrap();
+        assert_eq!(page1.len(), 2);
+
+        let last_key = &page1.last().unwrap().0;
+        let page2 = store
+            .scan_prefix("records/col/", Some(last_key), Some(2))
+            .unwrap();
+        assert_eq!(page2.len(), 2);
+        assert_ne!(page1[0].0, page2[0].0);
+    }
+
+    #[test]
+    fn test_column_scan_excludes_other_columns() {
+        let store = IndexedDbStore::new("test_db");
+        store
+            .apply_batch(vec![
+                WriteOp::Put("identity".to_string(), b"did:plc:test".to_vec()),
+                WriteOp::Put("records/app.bsky.feed.post/1".to_string(), b"a".to_vec()),
+                WriteOp::Put("commits/1".to_string(), b"c".to_vec()),
+            ])
+            .unwrap();
+
+        let records = store.column(Column::Records).scan_prefix("", None, None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, "records/app.bsky.feed.post/1");
+
+        let identity = store.column(Column::Identity).scan_prefix("", None, None).unwrap();
+        assert_eq!(identity.len(), 1);
+        assert_eq!(identity[0].0, "identity");
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let store = IndexedDbStore::new("test_db");
+        store
+            .apply_batch(vec![
+                WriteOp::Put("identity".to_string(), b"did:plc:test".to_vec()),
+                WriteOp::Put("commits/1".to_string(), b"commit-bytes".to_vec()),
+                WriteOp::Put("records/app.bsky.feed.post/1".to_string(), b"post".to_vec()),
+            ])
+            .unwrap();
+
+        let archive = store.export().unwrap();
+
+        let restored = IndexedDbStore::new("restored_db");
+        restored.import(&archive).unwrap();
+
+        assert_eq!(restored.get("identity").unwrap(), Some(b"did:plc:test".to_vec()));
+        assert_eq!(restored.get("commits/1").unwrap(), Some(b"commit-bytes".to_vec()));
+        assert_eq!(
+            restored.get("records/app.bsky.feed.post/1").unwrap(),
+            Some(b"post".to_vec())
+        );
+    }
 }
@@ -1,174 +1,299 @@
-use pds_core::{Crypto as CryptoTrait, Error, Result};
-use async_trait::async_trait;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
-use wasm_bindgen_futures::JsFuture;
-use web_sys::{window, Crypto, SubtleCrypto, CryptoKey, CryptoKeyPair};
-use js_sys::{Object, Reflect, Uint8Array, Array};
-use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
-use sha2::{Sha256, Digest};
-use base64::{Engine as _, engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}};
-use getrandom;
+//! Browser-persisted signing key.
+//!
+//! `Ed25519Crypto`/`Secp256k1Crypto` (from `pds-core`) have no notion of
+//! persistence — a fresh one is just a random keypair in memory, gone on
+//! the next page load. `WebCrypto` wraps whichever of the two is active and
+//! adds `localStorage`-backed save/restore plus BIP39 mnemonic backup, so a
+//! user's identity survives a reload and can be recovered on another
+//! device.
+
+use crate::error::{Result as WasmResult, WasmError};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use pds_core::did_key::KeyType;
+use pds_core::traits::{Crypto, Ed25519Crypto, Secp256k1Crypto};
+use web_sys::window;
 
 const KEYPAIR_STORAGE_KEY: &str = "pds_keypair";
-const DID_STORAGE_KEY: &str = "pds_did";
+const KEY_TYPE_STORAGE_KEY: &str = "pds_key_type";
+
+#[derive(Clone)]
+enum Signer {
+    Ed25519(Ed25519Crypto),
+    Secp256k1(Secp256k1Crypto),
+}
+
+impl Signer {
+    fn generate(key_type: KeyType) -> Self {
+        match key_type {
+            KeyType::Ed25519 => Signer::Ed25519(Ed25519Crypto::new()),
+            KeyType::Secp256k1 => Signer::Secp256k1(Secp256k1Crypto::new()),
+        }
+    }
+
+    fn from_private_key_bytes(key_type: KeyType, bytes: &[u8]) -> WasmResult<Self> {
+        let seed: &[u8; 32] = bytes
+            .try_into()
+            .map_err(|_| WasmError::Crypto("Invalid keypair length".to_string()))?;
+        Ok(match key_type {
+            KeyType::Ed25519 => Signer::Ed25519(Ed25519Crypto::from_bytes(seed)),
+            KeyType::Secp256k1 => Signer::Secp256k1(Secp256k1Crypto::from_bytes(seed)?),
+        })
+    }
+
+    fn private_key_bytes(&self) -> Vec<u8> {
+        match self {
+            Signer::Ed25519(c) => c.private_key_bytes(),
+            Signer::Secp256k1(c) => c.private_key_bytes(),
+        }
+    }
+
+    fn key_type(&self) -> KeyType {
+        match self {
+            Signer::Ed25519(_) => KeyType::Ed25519,
+            Signer::Secp256k1(_) => KeyType::Secp256k1,
+        }
+    }
+}
+
+impl Crypto for Signer {
+    fn sign(&self, data: &[u8]) -> pds_core::Result<Vec<u8>> {
+        match self {
+            Signer::Ed25519(c) => c.sign(data),
+            Signer::Secp256k1(c) => c.sign(data),
+        }
+    }
 
-/// WebCrypto-based cryptographic operations
+    fn verify(&self, data: &[u8], signature: &[u8], public_key: &[u8]) -> pds_core::Result<bool> {
+        match self {
+            Signer::Ed25519(c) => c.verify(data, signature, public_key),
+            Signer::Secp256k1(c) => c.verify(data, signature, public_key),
+        }
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        match self {
+            Signer::Ed25519(c) => c.public_key(),
+            Signer::Secp256k1(c) => c.public_key(),
+        }
+    }
+
+    fn key_type(&self) -> KeyType {
+        Signer::key_type(self)
+    }
+}
+
+/// Signing key with optional `localStorage` persistence. Constructing one
+/// (`new`, `generate_keypair_with`, `import_mnemonic`) never touches the browser —
+/// only [`load_or_create`](Self::load_or_create) and
+/// [`persist`](Self::persist) do, so the type stays usable (and testable)
+/// outside a DOM.
+#[derive(Clone)]
 pub struct WebCrypto {
-    storage: web_sys::Storage,
+    signer: Signer,
 }
 
 impl WebCrypto {
-    pub fn new() -> Result<Self> {
-        let window = window().ok_or_else(|| Error::Crypto("No window object".to_string()))?;
-        let storage = window
-            .local_storage()
-            .map_err(|_| Error::Crypto("Failed to access localStorage".to_string()))?
-            .ok_or_else(|| Error::Crypto("localStorage not available".to_string()))?;
-        
-        Ok(Self { storage })
-    }
-
-    fn get_stored_keypair(&self) -> Result<Option<Vec<u8>>> {
-        match self.storage.get_item(KEYPAIR_STORAGE_KEY) {
-            Ok(Some(data)) => {
-                let bytes = STANDARD.decode(&data)
-                    .map_err(|e| Error::Crypto(format!("Failed to decode keypair: {}", e)))?;
-                Ok(Some(bytes))
+    /// A fresh, unpersisted Ed25519 keypair.
+    pub fn new() -> Self {
+        Self {
+            signer: Signer::generate(KeyType::Ed25519),
+        }
+    }
+
+    /// A fresh, unpersisted keypair for `key_type`.
+    pub fn generate_keypair_with(key_type: KeyType) -> Self {
+        Self {
+            signer: Signer::generate(key_type),
+        }
+    }
+
+    /// Load the keypair persisted by a previous [`persist`](Self::persist)
+    /// call, or generate (and persist) a new Ed25519 one if none exists
+    /// yet.
+    pub fn load_or_create() -> WasmResult<Self> {
+        let storage = local_storage()?;
+
+        let key_type = storage
+            .get_item(KEY_TYPE_STORAGE_KEY)
+            .map_err(|_| WasmError::Crypto("Failed to read key type from storage".to_string()))?;
+        let keypair = storage
+            .get_item(KEYPAIR_STORAGE_KEY)
+            .map_err(|_| WasmError::Crypto("Failed to read keypair from storage".to_string()))?;
+
+        let loaded = match (key_type, keypair) {
+            (Some(key_type), Some(keypair)) => {
+                let key_type = parse_key_type(&key_type)?;
+                let bytes = STANDARD
+                    .decode(&keypair)
+                    .map_err(|e| WasmError::Crypto(format!("Failed to decode keypair: {}", e)))?;
+                Some(Self {
+                    signer: Signer::from_private_key_bytes(key_type, &bytes)?,
+                })
+            }
+            _ => None,
+        };
+
+        match loaded {
+            Some(crypto) => Ok(crypto),
+            None => {
+                let crypto = Self::new();
+                crypto.persist()?;
+                Ok(crypto)
             }
-            Ok(None) => Ok(None),
-            Err(_) => Err(Error::Crypto("Failed to read keypair from storage".to_string())),
         }
     }
 
-    fn store_keypair(&self, keypair: &[u8]) -> Result<()> {
-        let encoded = STANDARD.encode(keypair);
-        self.storage
+    /// Save this keypair to `localStorage`, overwriting whatever identity
+    /// was previously stored there.
+    pub fn persist(&self) -> WasmResult<()> {
+        let storage = local_storage()?;
+        let encoded = STANDARD.encode(self.signer.private_key_bytes());
+        storage
             .set_item(KEYPAIR_STORAGE_KEY, &encoded)
-            .map_err(|_| Error::Crypto("Failed to store keypair".to_string()))
+            .map_err(|_| WasmError::Crypto("Failed to store keypair".to_string()))?;
+        storage
+            .set_item(KEY_TYPE_STORAGE_KEY, key_type_name(self.signer.key_type()))
+            .map_err(|_| WasmError::Crypto("Failed to store key type".to_string()))?;
+        Ok(())
     }
 
-    fn get_stored_did(&self) -> Result<Option<String>> {
-        self.storage
-            .get_item(DID_STORAGE_KEY)
-            .map_err(|_| Error::Crypto("Failed to read DID from storage".to_string()))
+    /// Export this keypair's raw scalar as a 24-word BIP39 mnemonic, so a
+    /// user can write it down and restore their identity on another
+    /// device. Unlike an opaque exported blob, the entropy-to-mnemonic
+    /// mapping (with its appended checksum) is the standard BIP39 encoding,
+    /// so any compatible wallet could display the same words.
+    pub fn export_mnemonic(&self) -> WasmResult<String> {
+        let seed = self.signer.private_key_bytes();
+        let mnemonic = bip39::Mnemonic::from_entropy(&seed)
+            .map_err(|e| WasmError::Crypto(format!("Failed to encode mnemonic: {}", e)))?;
+        Ok(mnemonic.to_string())
     }
 
-    fn store_did(&self, did: &str) -> Result<()> {
-        self.storage
-            .set_item(DID_STORAGE_KEY, did)
-            .map_err(|_| Error::Crypto("Failed to store DID".to_string()))
+    /// Validate `phrase`'s BIP39 checksum and reconstruct the 32-byte
+    /// Ed25519 seed it encodes — the exact seed
+    /// [`export_mnemonic`](Self::export_mnemonic) exported, so the round
+    /// trip reproduces the identical identity for an Ed25519 keypair. The
+    /// phrase carries no key type: a secp256k1 keypair's exported scalar is
+    /// rebuilt here as an Ed25519 key, a different identity. Does not touch
+    /// `localStorage`; call [`persist`](Self::persist) to make it the
+    /// active stored keypair.
+    pub fn import_mnemonic(phrase: &str) -> WasmResult<Self> {
+        let mnemonic = bip39::Mnemonic::parse_normalized(phrase)
+            .map_err(|e| WasmError::Crypto(format!("Invalid mnemonic: {}", e)))?;
+
+        let entropy = mnemonic.to_entropy();
+        if entropy.len() != 32 {
+            return Err(WasmError::Crypto(format!(
+                "Expected a 24-word (32-byte) mnemonic, got {} bytes",
+                entropy.len()
+            )));
+        }
+
+        Ok(Self {
+            signer: Signer::from_private_key_bytes(KeyType::Ed25519, &entropy)?,
+        })
     }
 
-    fn bytes_to_did(&self, public_key: &[u8]) -> String {
-        // Create did:key from public key (simplified)
-        // In real implementation, this should use multibase/multicodec encoding
-        format!("did:key:z{}", URL_SAFE_NO_PAD.encode(public_key))
+    /// This keypair's `did:key` identifier.
+    pub fn did(&self) -> WasmResult<String> {
+        Ok(pds_core::did_key::bytes_to_did(
+            self.signer.key_type(),
+            &self.signer.public_key(),
+        )?)
     }
+}
 
-    fn did_to_bytes(&self, did: &str) -> Result<Vec<u8>> {
-        // Extract public key from did:key
-        let key_part = did.strip_prefix("did:key:z")
-            .ok_or_else(|| Error::InvalidDid(did.to_string()))?;
-        URL_SAFE_NO_PAD.decode(key_part)
-            .map_err(|e| Error::InvalidDid(format!("Failed to decode DID: {}", e)))
+impl Default for WebCrypto {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-#[async_trait(?Send)]
-impl CryptoTrait for WebCrypto {
-    async fn generate_keypair(&self) -> Result<String> {
-        // Check if keypair already exists
-        if let Some(did) = self.get_stored_did()? {
-            return Ok(did);
-        }
+impl Crypto for WebCrypto {
+    fn sign(&self, data: &[u8]) -> pds_core::Result<Vec<u8>> {
+        self.signer.sign(data)
+    }
 
-        // Generate Ed25519 keypair using ed25519-dalek
-        // Generate random bytes for the secret key
-        let mut secret_bytes = [0u8; 32];
-        getrandom::getrandom(&mut secret_bytes)
-            .map_err(|e| Error::Crypto(format!("Failed to generate random bytes: {}", e)))?;
-        
-        let signing_key = SigningKey::from_bytes(&secret_bytes);
-        let verifying_key = signing_key.verifying_key();
-        
-        // Store the keypair (32 bytes secret key)
-        self.store_keypair(&secret_bytes)?;
-        
-        // Create and store DID from public key
-        let did = self.bytes_to_did(verifying_key.as_bytes());
-        self.store_did(&did)?;
-        
-        Ok(did)
-    }
-
-    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
-        // Get stored keypair
-        let keypair_bytes = self
-            .get_stored_keypair()?
-            .ok_or_else(|| Error::Crypto("No keypair available".to_string()))?;
-        
-        // Reconstruct signing key
-        let signing_key = SigningKey::from_bytes(
-            keypair_bytes.as_slice().try_into()
-                .map_err(|_| Error::Crypto("Invalid keypair format".to_string()))?
-        );
-        
-        // Sign the data
-        let signature = signing_key.sign(data);
-        Ok(signature.to_bytes().to_vec())
-    }
-
-    async fn verify(&self, data: &[u8], signature: &[u8], public_key: &str) -> Result<bool> {
-        // Extract public key bytes from DID
-        let pubkey_bytes = self.did_to_bytes(public_key)?;
-        
-        // Reconstruct verifying key
-        let verifying_key = VerifyingKey::from_bytes(
-            pubkey_bytes.as_slice().try_into()
-                .map_err(|_| Error::Crypto("Invalid public key format".to_string()))?
-        )
-        .map_err(|e| Error::Crypto(format!("Failed to create verifying key: {}", e)))?;
-        
-        // Reconstruct signature
-        let sig = Signature::from_bytes(
-            signature.try_into()
-                .map_err(|_| Error::InvalidSignature)?
-        );
-        
-        // Verify
-        Ok(verifying_key.verify(data, &sig).is_ok())
-    }
-
-    async fn get_did(&self) -> Result<Option<String>> {
-        self.get_stored_did()
-    }
-
-    async fn export_keypair(&self) -> Result<Vec<u8>> {
-        self.get_stored_keypair()?
-            .ok_or_else(|| Error::Crypto("No keypair to export".to_string()))
-    }
-
-    async fn import_keypair(&self, data: &[u8]) -> Result<String> {
-        // Validate keypair length (32 bytes for Ed25519)
-        if data.len() != 32 {
-            return Err(Error::Crypto("Invalid keypair length".to_string()));
-        }
-        
-        // Reconstruct keys to validate
-        let signing_key = SigningKey::from_bytes(
-            data.try_into()
-                .map_err(|_| Error::Crypto("Invalid keypair format".to_string()))?
-        );
-        let verifying_key = signing_key.verifying_key();
-        
-        // Store keypair
-        self.store_keypair(data)?;
-        
-        // Create and store DID
-        let did = self.bytes_to_did(verifying_key.as_bytes());
-        self.store_did(&did)?;
-        
-        Ok(did)
+    fn verify(&self, data: &[u8], signature: &[u8], public_key: &[u8]) -> pds_core::Result<bool> {
+        self.signer.verify(data, signature, public_key)
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.signer.public_key()
+    }
+
+    fn key_type(&self) -> KeyType {
+        self.signer.key_type()
+    }
+}
+
+fn local_storage() -> WasmResult<web_sys::Storage> {
+    let window = window().ok_or_else(|| WasmError::Crypto("No window object".to_string()))?;
+    window
+        .local_storage()
+        .map_err(|_| WasmError::Crypto("Failed to access localStorage".to_string()))?
+        .ok_or_else(|| WasmError::Crypto("localStorage not available".to_string()))
+}
+
+fn key_type_name(key_type: KeyType) -> &'static str {
+    match key_type {
+        KeyType::Ed25519 => "ed25519",
+        KeyType::Secp256k1 => "secp256k1",
+    }
+}
+
+fn parse_key_type(name: &str) -> WasmResult<KeyType> {
+    match name {
+        "ed25519" => Ok(KeyType::Ed25519),
+        "secp256k1" => Ok(KeyType::Secp256k1),
+        other => Err(WasmError::Crypto(format!("Unknown stored key type: {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let crypto = WebCrypto::new();
+        let data = b"test message";
+        let signature = crypto.sign(data).unwrap();
+        assert!(crypto.verify(data, &signature, &crypto.public_key()).unwrap());
+    }
+
+    #[test]
+    fn test_mnemonic_round_trip_preserves_identity() {
+        let original = WebCrypto::new();
+        let phrase = original.export_mnemonic().unwrap();
+
+        let restored = WebCrypto::import_mnemonic(&phrase).unwrap();
+        assert_eq!(restored.public_key(), original.public_key());
+    }
+
+    #[test]
+    fn test_import_mnemonic_rejects_wrong_word_count() {
+        let phrase = "abandon abandon abandon abandon abandon abandon";
+        assert!(WebCrypto::import_mnemonic(phrase).is_err());
+    }
+
+    #[test]
+    fn test_import_mnemonic_rejects_bad_checksum() {
+        // Valid words, but a wrong checksum: all-zero entropy encodes as
+        // `abandon x23 art`, so `abandon x24` fails the checksum.
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                       abandon abandon abandon abandon abandon abandon abandon abandon \
+                       abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert!(WebCrypto::import_mnemonic(phrase).is_err());
+    }
+
+    #[test]
+    fn test_generate_keypair_with_secp256k1() {
+        let crypto = WebCrypto::generate_keypair_with(KeyType::Secp256k1);
+        assert_eq!(crypto.key_type(), KeyType::Secp256k1);
+
+        let data = b"test message";
+        let signature = crypto.sign(data).unwrap();
+        assert!(crypto.verify(data, &signature, &crypto.public_key()).unwrap());
     }
 }